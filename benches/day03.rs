@@ -0,0 +1,24 @@
+use adventofcode_24::day03::instruction::Tokenizer;
+use criterion::{Criterion, Throughput, black_box, criterion_group, criterion_main};
+
+const INPUT: &str = include_str!("../src/day03/input.txt");
+
+fn bench_tokenize(c: &mut Criterion) {
+    let tokenizer = Tokenizer::default();
+    let large = INPUT.repeat(64);
+
+    for (name, input) in [("input", INPUT), ("input x64", large.as_str())] {
+        let mut group = c.benchmark_group(format!("day03 tokenize {name}"));
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_function("naive", |b| {
+            b.iter(|| tokenizer.tokenize_naive(black_box(input)));
+        });
+        group.bench_function("memchr", |b| {
+            b.iter(|| tokenizer.tokenize_spanned(black_box(input)));
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_tokenize);
+criterion_main!(benches);
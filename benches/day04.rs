@@ -0,0 +1,48 @@
+use adventofcode_24::day04::{
+    bytes::ByteGrid,
+    count_cross, count_word,
+    grid::{Shape, parse_chars},
+};
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+
+const SIZE: usize = 10_000;
+
+fn random_grid(size: usize) -> String {
+    let mut state: u64 = 0x2024_0004;
+    let mut grid = String::with_capacity((size + 1) * size);
+    for _ in 0..size {
+        for _ in 0..size {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            grid.push(['X', 'M', 'A', 'S'][(state >> 32) as usize % 4]);
+        }
+        grid.push('\n');
+    }
+    grid
+}
+
+fn bench_count(c: &mut Criterion) {
+    let input = random_grid(SIZE);
+    let chars = parse_chars(&input, Shape::Rectangular).unwrap();
+    let bytes = ByteGrid::parse(&input).unwrap();
+    let scans = bytes.line_scans();
+
+    let mut group = c.benchmark_group(format!("day04 {SIZE}x{SIZE}"));
+    group.sample_size(10);
+    group.bench_function("count_word", |b| b.iter(|| count_word(black_box(&chars))));
+    group.bench_function("line_scans + count_word", |b| {
+        b.iter(|| black_box(&bytes).line_scans().count_word(b"XMAS"));
+    });
+    group.bench_function("precomputed count_word", |b| {
+        b.iter(|| black_box(&scans).count_word(b"XMAS"));
+    });
+    group.bench_function("count_cross", |b| b.iter(|| count_cross(black_box(&chars))));
+    group.bench_function("byte grid count_cross", |b| {
+        b.iter(|| black_box(&bytes).count_cross());
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_count);
+criterion_main!(benches);
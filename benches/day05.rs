@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use adventofcode_24::day05::{order_updates, order_updates_naive, precedence::Precedence};
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+
+const PAGES: i32 = 2_000;
+const UPDATES: usize = 2_000;
+const UPDATE_LEN: usize = 51;
+
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() >> 32) as usize % n
+    }
+}
+
+/// Rules between random pairs of pages, always pointing from the smaller to
+/// the larger page so they never form a cycle, and shuffled updates.
+fn synthetic_input() -> (HashMap<i32, Vec<i32>>, Vec<Vec<i32>>) {
+    let mut rng = XorShift(0x2024_0005);
+    let mut rules: HashMap<i32, Vec<i32>> = HashMap::new();
+    for before in 0..PAGES {
+        for after in before + 1..PAGES {
+            if rng.below(20) == 0 {
+                rules.entry(before).or_default().push(after);
+            }
+        }
+    }
+
+    let updates = (0..UPDATES)
+        .map(|_| {
+            let mut update: Vec<i32> = Vec::with_capacity(UPDATE_LEN);
+            while update.len() < UPDATE_LEN {
+                let page = rng.below(PAGES as usize) as i32;
+                if !update.contains(&page) {
+                    update.push(page);
+                }
+            }
+            update
+        })
+        .collect();
+    (rules, updates)
+}
+
+fn bench_order(c: &mut Criterion) {
+    let (rules, updates) = synthetic_input();
+    let precedence = Precedence::new(&rules);
+
+    let mut group = c.benchmark_group(format!("day05 {PAGES} pages {UPDATES} updates"));
+    group.sample_size(10);
+    group.bench_function("Precedence::new", |b| {
+        b.iter(|| Precedence::new(black_box(&rules)));
+    });
+    group.bench_function("order_updates_naive", |b| {
+        b.iter(|| {
+            for update in &updates {
+                black_box(order_updates_naive(update, black_box(&rules)).unwrap());
+            }
+        });
+    });
+    group.bench_function("order_updates", |b| {
+        b.iter(|| {
+            for update in &updates {
+                black_box(order_updates(update, black_box(&precedence)).unwrap());
+            }
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_order);
+criterion_main!(benches);
@@ -1,5 +1,7 @@
 use std::{error::Error, fs};
 
+use crate::parse::{columns, lines, parse_lines, signed};
+
 fn calculate_distance(array_one: &mut [i32], array_two: &mut [i32]) -> i32 {
     array_one.sort_unstable();
     array_two.sort_unstable();
@@ -30,17 +32,11 @@ fn calculate_score(array_one: &mut [i32], array_two: &mut [i32]) -> i32 {
 pub fn solve_day01(path: &str) -> Result<(i32, i32), Box<dyn Error>> {
     let content = fs::read_to_string(path)?;
 
-    let mut array_one = Vec::new();
-    let mut array_two = Vec::new();
-    for line in content.split('\n') {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() == 2 {
-            let a = parts[0];
-            let b = parts[1];
-            array_one.push(a.trim().parse()?);
-            array_two.push(b.trim().parse()?);
-        }
-    }
+    let (mut array_one, mut array_two): (Vec<i32>, Vec<i32>) =
+        parse_lines(&lines(&content), columns::<_, 2>(signed::<i32>))?
+            .into_iter()
+            .map(|[a, b]| (a, b))
+            .unzip();
 
     if array_one.is_empty() || array_one.len() != array_two.len() {
         Err(Box::new(std::io::Error::new(
@@ -1,30 +1,44 @@
 use std::{error::Error, fs};
 
+use crate::parse::{lines, parse_lines, signed, whitespace_list};
+
 /// Solves the problem for day 02.
 ///
 /// # Errors
 ///
 /// This function will return an error if the file cannot be read or if the input is invalid.
-#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
 pub fn solve_day02(path: &str) -> Result<(i32, i32), Box<dyn Error>> {
+    solve_day02_with_policy(path, &SafetyPolicy::default())
+}
+
+/// Solves the problem for day 02 using a custom [`SafetyPolicy`].
+///
+/// Part one is evaluated without any tolerance, part two allows removing up to
+/// `policy.tolerance` levels from each report.
+///
+/// # Errors
+///
+/// This function will return an error if the file cannot be read or if the input is invalid.
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+pub fn solve_day02_with_policy(
+    path: &str,
+    policy: &SafetyPolicy,
+) -> Result<(i32, i32), Box<dyn Error>> {
     let content = fs::read_to_string(path)?;
 
-    let data: Vec<Vec<i32>> = content
-        .split_terminator('\n')
-        .map(|line| {
-            line.split_whitespace()
-                .map(str::parse)
-                .collect::<Result<Vec<_>, _>>()
-        })
-        .collect::<Result<Vec<_>, _>>()?;
+    let data: Vec<Vec<i32>> = parse_lines(&lines(&content), whitespace_list(signed))?;
 
-    let result = verify_all_reports(&data);
+    let strict = SafetyPolicy {
+        tolerance: 0,
+        ..*policy
+    };
+    let result = verify_all_reports(&data, &strict);
     let result1 = result
         .iter()
         .filter(|safety| *safety != &Safety::Unsafe)
         .count() as i32;
 
-    let result = verify_tolerance(&data);
+    let result = verify_tolerance(&data, policy);
     let result2 = result
         .iter()
         .filter(|safety| *safety != &Safety::Unsafe)
@@ -33,6 +47,115 @@ pub fn solve_day02(path: &str) -> Result<(i32, i32), Box<dyn Error>> {
     Ok((result1, result2))
 }
 
+/// The direction a report is allowed to move in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trend {
+    Increasing,
+    Decreasing,
+    Either,
+}
+
+impl Trend {
+    const fn allows_increasing(self) -> bool {
+        matches!(self, Self::Increasing | Self::Either)
+    }
+
+    const fn allows_decreasing(self) -> bool {
+        matches!(self, Self::Decreasing | Self::Either)
+    }
+}
+
+impl std::str::FromStr for Trend {
+    type Err = std::io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "increasing" | "inc" => Ok(Self::Increasing),
+            "decreasing" | "dec" => Ok(Self::Decreasing),
+            "either" | "any" => Ok(Self::Either),
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Invalid trend '{s}'. Expected one of: increasing, decreasing, either"),
+            )),
+        }
+    }
+}
+
+/// Thresholds a report has to satisfy to be considered safe.
+///
+/// The default matches the puzzle: adjacent levels differ by `1..=3`, the
+/// report is either strictly increasing or strictly decreasing and a single
+/// level may be removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SafetyPolicy {
+    min_step: i32,
+    max_step: i32,
+    trend: Trend,
+    tolerance: usize,
+}
+
+impl Default for SafetyPolicy {
+    fn default() -> Self {
+        Self {
+            min_step: 1,
+            max_step: 3,
+            trend: Trend::Either,
+            tolerance: 1,
+        }
+    }
+}
+
+impl SafetyPolicy {
+    /// Creates a new policy.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `min_step` is smaller than 1 or greater than `max_step`.
+    pub fn new(
+        min_step: i32,
+        max_step: i32,
+        trend: Trend,
+        tolerance: usize,
+    ) -> Result<Self, Box<dyn Error>> {
+        if min_step < 1 || min_step > max_step {
+            return Err(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "Invalid step range {min_step}..={max_step}. Steps must satisfy 1 <= min <= max"
+                ),
+            )));
+        }
+
+        Ok(Self {
+            min_step,
+            max_step,
+            trend,
+            tolerance,
+        })
+    }
+
+    pub const fn min_step(&self) -> i32 {
+        self.min_step
+    }
+
+    pub const fn max_step(&self) -> i32 {
+        self.max_step
+    }
+
+    pub const fn trend(&self) -> Trend {
+        self.trend
+    }
+
+    /// The number of levels that may be removed from a report in part two.
+    pub const fn tolerance(&self) -> usize {
+        self.tolerance
+    }
+
+    fn allows_step(&self, step: i64) -> bool {
+        (i64::from(self.min_step)..=i64::from(self.max_step)).contains(&step)
+    }
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum Safety {
@@ -41,107 +164,91 @@ enum Safety {
 }
 
 #[allow(dead_code)]
-fn verify_all_reports(reports: &[Vec<i32>]) -> Vec<Safety> {
+fn verify_all_reports(reports: &[Vec<i32>], policy: &SafetyPolicy) -> Vec<Safety> {
     reports
         .iter()
-        .map(|report| verify_reports(report))
+        .map(|report| verify_reports(report, policy))
         .collect()
 }
 
 #[allow(dead_code)]
-fn verify_tolerance(reports: &[Vec<i32>]) -> Vec<Safety> {
+fn verify_tolerance(reports: &[Vec<i32>], policy: &SafetyPolicy) -> Vec<Safety> {
     reports
         .iter()
-        // .filter(|report| verify_reports(report) == Safety::Unsafe)
-        .map(|report| {
-            let rep = verify_reports(report);
-
-            if rep == Safety::Safe {
-                return Safety::Safe;
-            }
-
-            if report
-                .iter()
-                .enumerate()
-                .map(|(index, _)| {
-                    let temp: Vec<i32> = report
-                        .iter()
-                        .enumerate()
-                        .filter(|(i, _)| *i != index)
-                        .map(|(_, r)| *r)
-                        .collect();
-                    // dbg!(&report, index, &temp);
-                    verify_reports(&temp)
-                })
-                .any(|r| r == Safety::Safe)
-            {
-                Safety::Safe
-            } else {
-                Safety::Unsafe
-            }
-        })
+        .map(|report| verify_removing(report, policy, policy.tolerance))
         .collect()
 }
 
-#[allow(dead_code)]
-fn verify_reports(reports: &[i32]) -> Safety {
-    const MAX: i32 = 3;
-    const MIN: i32 = 1;
+/// Checks whether `report` becomes safe after removing at most `remaining` levels.
+///
+/// For every allowed trend this keeps, per level, the minimum number of removals
+/// needed for a safe prefix ending in that level. Only the `remaining + 1` levels
+/// before it can be its predecessor, so the whole check runs in `O(n * k)`.
+fn verify_removing(report: &[i32], policy: &SafetyPolicy, remaining: usize) -> Safety {
+    let len = report.len();
+    if len <= remaining.saturating_add(1) {
+        return Safety::Safe;
+    }
 
-    for (index, report) in reports.iter().enumerate() {
-        if index == 0 {
-            continue;
-        }
+    let trends = [
+        (policy.trend.allows_increasing(), 1),
+        (policy.trend.allows_decreasing(), -1),
+    ];
 
-        let previous_report = reports[index - 1];
-        if index == reports.len() - 1 {
-            match (*report, previous_report) {
-                (r, p) if r > p => {
-                    let level = r - p;
-                    if !(MIN..=MAX).contains(&level) {
-                        return Safety::Unsafe;
-                    }
-                }
-                (r, p) if r < p => {
-                    let level = p - r;
-                    if !(MIN..=MAX).contains(&level) {
-                        return Safety::Unsafe;
-                    }
+    let mut removals = vec![0; len];
+    for (_, sign) in trends.iter().filter(|(allowed, _)| *allowed) {
+        for index in 0..len {
+            removals[index] = index;
+            for previous in index.saturating_sub(remaining.saturating_add(1))..index {
+                if policy.allows_step(step(report[previous], report[index]) * sign) {
+                    removals[index] =
+                        removals[index].min(removals[previous] + index - previous - 1);
                 }
-                (r, p) if r == p => return Safety::Unsafe,
-                _ => (),
             }
-            return Safety::Safe;
         }
-        let next_report = reports[index + 1];
 
-        match (*report, previous_report, next_report) {
-            (r, p, n) if r > p && r < n => {
-                let level = r - p;
-                if !(MIN..=MAX).contains(&level) {
-                    return Safety::Unsafe;
-                }
-            }
-            (r, p, n) if r < p && r > n => {
-                let level = p - r;
-                if !(MIN..=MAX).contains(&level) {
-                    return Safety::Unsafe;
-                }
-            }
-            (r, p, n) if (r < p && r < n) || (r > p && r > n) => {
-                return Safety::Unsafe;
-            }
-            (r, p, n) if r == p || r == n => return Safety::Unsafe,
-            _ => (),
+        if removals
+            .iter()
+            .enumerate()
+            .any(|(index, removed)| removed + len - 1 - index <= remaining)
+        {
+            return Safety::Safe;
         }
     }
 
-    Safety::Safe
+    Safety::Unsafe
+}
+
+/// The difference between two levels, widened so it cannot overflow.
+fn step(from: i32, to: i32) -> i64 {
+    i64::from(to) - i64::from(from)
+}
+
+/// Checks a single report against `policy`.
+///
+/// A report is safe if every difference between adjacent levels lies within the
+/// allowed step range in one allowed direction. Empty and single-level reports
+/// have no differences and are therefore always safe.
+fn verify_reports(reports: &[i32], policy: &SafetyPolicy) -> Safety {
+    let steps_allowed = |sign: i64| {
+        reports
+            .windows(2)
+            .all(|pair| policy.allows_step(step(pair[0], pair[1]) * sign))
+    };
+
+    if (policy.trend.allows_increasing() && steps_allowed(1))
+        || (policy.trend.allows_decreasing() && steps_allowed(-1))
+    {
+        Safety::Safe
+    } else {
+        Safety::Unsafe
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     fn test_day02_part_one() {
@@ -153,25 +260,29 @@ mod test {
             vec![8, 6, 4, 4, 1],
             vec![1, 3, 6, 7, 9],
         ];
+        let policy = SafetyPolicy {
+            tolerance: 0,
+            ..SafetyPolicy::default()
+        };
 
         let want = Safety::Safe;
-        let got = verify_reports(&input[0]);
+        let got = verify_reports(&input[0], &policy);
 
         assert_eq!(want, got);
 
         let want = Safety::Unsafe;
 
-        let got = verify_reports(&input[1]);
+        let got = verify_reports(&input[1], &policy);
         assert_eq!(want, got);
-        let got = verify_reports(&input[2]);
+        let got = verify_reports(&input[2], &policy);
         assert_eq!(want, got);
-        let got = verify_reports(&input[3]);
+        let got = verify_reports(&input[3], &policy);
         assert_eq!(want, got);
-        let got = verify_reports(&input[4]);
+        let got = verify_reports(&input[4], &policy);
         assert_eq!(want, got);
 
         let want = 2;
-        let got = verify_all_reports(&input);
+        let got = verify_all_reports(&input, &policy);
         assert_eq!(
             want,
             got.iter()
@@ -179,7 +290,7 @@ mod test {
                 .count()
         );
 
-        let got = verify_all_reports(&input);
+        let got = verify_all_reports(&input, &policy);
 
         assert_eq!(
             want,
@@ -202,7 +313,7 @@ mod test {
 
         let want = 4;
 
-        let got = verify_tolerance(&input);
+        let got = verify_tolerance(&input, &SafetyPolicy::default());
         assert_eq!(input.len(), got.len());
 
         assert_eq!(
@@ -212,4 +323,209 @@ mod test {
                 .count()
         );
     }
+
+    #[test]
+    fn test_day02_policy() {
+        let input = vec![
+            vec![7, 6, 4, 2, 1],
+            vec![1, 2, 7, 8, 9],
+            vec![9, 7, 6, 2, 1],
+            vec![1, 3, 2, 4, 5],
+            vec![8, 6, 4, 4, 1],
+            vec![1, 3, 6, 7, 9],
+        ];
+
+        let policy = SafetyPolicy::new(1, 3, Trend::Increasing, 0).unwrap();
+        let got = verify_all_reports(&input, &policy);
+        assert_eq!(
+            vec![
+                Safety::Unsafe,
+                Safety::Unsafe,
+                Safety::Unsafe,
+                Safety::Unsafe,
+                Safety::Unsafe,
+                Safety::Safe
+            ],
+            got
+        );
+
+        let policy = SafetyPolicy::new(1, 5, Trend::Either, 0).unwrap();
+        assert_eq!(Safety::Safe, verify_reports(&input[1], &policy));
+        assert_eq!(Safety::Safe, verify_reports(&input[2], &policy));
+
+        let policy = SafetyPolicy::new(1, 3, Trend::Either, 2).unwrap();
+        assert_eq!(
+            Safety::Safe,
+            verify_removing(&[1, 2, 9, 9, 3, 4], &policy, 2)
+        );
+        assert_eq!(
+            Safety::Unsafe,
+            verify_removing(&[1, 2, 9, 9, 3, 4], &policy, 1)
+        );
+
+        let policy = SafetyPolicy::new(1, 3, Trend::Either, usize::MAX).unwrap();
+        assert_eq!(
+            Safety::Safe,
+            verify_removing(&[1, 2, 9, 9, 3, 4], &policy, usize::MAX)
+        );
+        assert_eq!(
+            Safety::Safe,
+            verify_removing(&[1, i32::MIN, 2, 3], &policy, 1)
+        );
+        assert_eq!(
+            Safety::Unsafe,
+            verify_removing(&[i32::MIN, i32::MAX, 1, i32::MIN], &policy, 1)
+        );
+
+        assert!(SafetyPolicy::new(0, 3, Trend::Either, 1).is_err());
+        assert!(SafetyPolicy::new(4, 3, Trend::Either, 1).is_err());
+    }
+
+    fn verify_removing_brute_force(
+        report: &[i32],
+        policy: &SafetyPolicy,
+        remaining: usize,
+    ) -> Safety {
+        if verify_reports(report, policy) == Safety::Safe {
+            return Safety::Safe;
+        }
+
+        if remaining == 0 {
+            return Safety::Unsafe;
+        }
+
+        if report
+            .iter()
+            .enumerate()
+            .map(|(index, _)| {
+                let temp: Vec<i32> = report
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| *i != index)
+                    .map(|(_, r)| *r)
+                    .collect();
+                verify_removing_brute_force(&temp, policy, remaining - 1)
+            })
+            .any(|r| r == Safety::Safe)
+        {
+            Safety::Safe
+        } else {
+            Safety::Unsafe
+        }
+    }
+
+    fn report_strategy() -> impl Strategy<Value = Vec<i32>> {
+        let level = prop_oneof![
+            8 => 0..12,
+            1 => prop_oneof![Just(i32::MIN), Just(i32::MIN + 1), Just(i32::MAX - 1), Just(i32::MAX)],
+        ];
+        proptest::collection::vec(level, 0..9)
+    }
+
+    fn policy_strategy() -> impl Strategy<Value = SafetyPolicy> {
+        (
+            1..3,
+            0..3,
+            prop_oneof![
+                Just(Trend::Increasing),
+                Just(Trend::Decreasing),
+                Just(Trend::Either)
+            ],
+            0usize..4,
+        )
+            .prop_map(|(min_step, range, trend, tolerance)| SafetyPolicy {
+                min_step,
+                max_step: min_step + range,
+                trend,
+                tolerance,
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn test_day02_linear_matches_brute_force(
+            report in report_strategy(),
+            policy in policy_strategy(),
+        ) {
+            prop_assert_eq!(
+                verify_removing_brute_force(&report, &policy, policy.tolerance),
+                verify_removing(&report, &policy, policy.tolerance)
+            );
+        }
+    }
+
+    fn reference_safety(report: &[i32], policy: &SafetyPolicy) -> Safety {
+        let differences: Vec<i64> = report
+            .windows(2)
+            .map(|w| i64::from(w[1]) - i64::from(w[0]))
+            .collect();
+        let in_range = differences
+            .iter()
+            .all(|d| (i64::from(policy.min_step)..=i64::from(policy.max_step)).contains(&d.abs()));
+        let increasing = differences.iter().all(|d| *d > 0);
+        let decreasing = differences.iter().all(|d| *d < 0);
+
+        let safe = in_range
+            && match policy.trend {
+                Trend::Increasing => increasing,
+                Trend::Decreasing => decreasing,
+                Trend::Either => increasing || decreasing,
+            };
+
+        if safe { Safety::Safe } else { Safety::Unsafe }
+    }
+
+    fn all_reports(max_len: usize, max_level: i32) -> Vec<Vec<i32>> {
+        let mut reports = vec![vec![]];
+        let mut current = vec![vec![]];
+        for _ in 0..max_len {
+            current = current
+                .iter()
+                .flat_map(|report: &Vec<i32>| {
+                    (0..=max_level).map(move |level| {
+                        let mut next = report.clone();
+                        next.push(level);
+                        next
+                    })
+                })
+                .collect();
+            reports.extend(current.iter().cloned());
+        }
+        reports
+    }
+
+    #[test]
+    fn test_day02_empty_and_single_level() {
+        let policy = SafetyPolicy::default();
+        assert_eq!(Safety::Safe, verify_reports(&[], &policy));
+        assert_eq!(Safety::Safe, verify_reports(&[5], &policy));
+        assert_eq!(Safety::Safe, verify_removing(&[], &policy, 0));
+        assert_eq!(Safety::Safe, verify_removing(&[5], &policy, 0));
+    }
+
+    #[test]
+    fn test_day02_exhaustive_small_reports() {
+        let policies = [
+            SafetyPolicy::default(),
+            SafetyPolicy::new(1, 1, Trend::Increasing, 0).unwrap(),
+            SafetyPolicy::new(2, 4, Trend::Decreasing, 0).unwrap(),
+        ];
+
+        let extremes = [
+            vec![i32::MAX, i32::MIN],
+            vec![i32::MIN, i32::MAX],
+            vec![i32::MIN, i32::MIN + 2, i32::MIN + 4],
+            vec![i32::MAX, i32::MAX - 1, i32::MAX - 3],
+            vec![i32::MAX, 0, i32::MIN],
+        ];
+        for report in all_reports(5, 6).into_iter().chain(extremes) {
+            for policy in &policies {
+                assert_eq!(
+                    reference_safety(&report, policy),
+                    verify_reports(&report, policy),
+                    "{report:?} with {policy:?}"
+                );
+            }
+        }
+    }
 }
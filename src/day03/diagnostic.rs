@@ -0,0 +1,57 @@
+use std::fmt;
+
+use nom::error::{VerboseError, VerboseErrorKind};
+
+/// A near-miss instruction: its prefix matched, but the rest did not parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Byte offset where parsing failed.
+    pub position: usize,
+    /// What the parser expected at `position`.
+    pub expected: String,
+    /// The character found at `position`, if any.
+    pub found: Option<char>,
+    /// The text from the start of the instruction up to `position`.
+    pub partial: String,
+}
+
+impl Diagnostic {
+    /// Builds a diagnostic for a parser that started at `start` in `input` and
+    /// failed with `error`.
+    pub fn new(input: &str, start: usize, error: &nom::Err<VerboseError<&str>>) -> Self {
+        let errors = match error {
+            nom::Err::Error(e) | nom::Err::Failure(e) => e.errors.as_slice(),
+            nom::Err::Incomplete(_) => &[],
+        };
+
+        let position = errors
+            .first()
+            .map_or(input.len(), |(rest, _)| input.len() - rest.len());
+        let expected = errors
+            .iter()
+            .find_map(|(_, kind)| match kind {
+                VerboseErrorKind::Char(c) => Some(format!("'{c}'")),
+                VerboseErrorKind::Context(context) => Some((*context).to_string()),
+                VerboseErrorKind::Nom(_) => None,
+            })
+            .unwrap_or_else(|| "instruction".to_string());
+
+        Self {
+            position,
+            expected,
+            found: input[position..].chars().next(),
+            partial: input[start..position].to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "byte {}: expected {}", self.position, self.expected)?;
+        match self.found {
+            Some(c) => write!(f, ", found {c:?}")?,
+            None => write!(f, ", found end of input")?,
+        }
+        write!(f, " after `{}`", self.partial)
+    }
+}
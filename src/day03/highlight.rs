@@ -0,0 +1,33 @@
+use super::instruction::Instruction;
+use super::vm::Annotated;
+
+const ENABLED: &str = "\x1b[32m";
+const DISABLED: &str = "\x1b[31m";
+const CONTROL: &str = "\x1b[33m";
+const RESET: &str = "\x1b[0m";
+
+/// Renders `input` with ANSI colours for every annotated instruction.
+///
+/// Enabled multiplications are green, disabled ones red and every other
+/// instruction, e.g. `do()`/`don't()`, yellow. Everything else is printed unchanged.
+pub fn highlight(input: &str, annotated: &[Annotated]) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut position = 0;
+
+    for Annotated { token, enabled } in annotated {
+        let colour = match (token.instruction.opcode, enabled) {
+            (Instruction::MUL, true) => ENABLED,
+            (Instruction::MUL, false) => DISABLED,
+            _ => CONTROL,
+        };
+
+        output.push_str(&input[position..token.span.start]);
+        output.push_str(colour);
+        output.push_str(&input[token.span.clone()]);
+        output.push_str(RESET);
+        position = token.span.end;
+    }
+
+    output.push_str(&input[position..]);
+    output
+}
@@ -0,0 +1,307 @@
+use std::ops::Range;
+
+use memchr::{memchr, memchr2, memchr3};
+use nom::IResult;
+use nom::bytes::complete::tag;
+use nom::character::complete::char;
+use nom::combinator::value;
+use nom::error::{VerboseError, context};
+use nom::sequence::{delimited, preceded, separated_pair};
+
+use super::diagnostic::Diagnostic;
+use super::operand::OperandPolicy;
+
+/// A decoded instruction: the name of its opcode and its operands.
+///
+/// The set of opcodes is open. A new instruction, with any number of
+/// operands, only needs a parser registered with [`Tokenizer::register`] and a
+/// rule registered with [`super::vm::Vm::register`] that act on the same opcode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    pub opcode: &'static str,
+    operands: Vec<i32>,
+}
+
+impl Instruction {
+    pub const MUL: &'static str = "mul";
+    pub const DO: &'static str = "do";
+    pub const DONT: &'static str = "don't";
+
+    pub fn new(opcode: &'static str, operands: &[i32]) -> Self {
+        Self {
+            opcode,
+            operands: operands.to_vec(),
+        }
+    }
+
+    pub fn mul(a: i32, b: i32) -> Self {
+        Self::new(Self::MUL, &[a, b])
+    }
+
+    pub fn operands(&self) -> &[i32] {
+        &self.operands
+    }
+}
+
+/// An [`Instruction`] together with the byte range it occupies in the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub instruction: Instruction,
+    pub span: Range<usize>,
+}
+
+/// The result of tokenizing a (partial) input.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Scan {
+    pub tokens: Vec<Token>,
+    pub diagnostics: Vec<Diagnostic>,
+    /// Byte offset the scan stopped at.
+    pub consumed: usize,
+}
+
+pub type ParseResult<'a> = IResult<&'a str, Instruction, VerboseError<&'a str>>;
+
+pub type InstructionParser = for<'a> fn(&'a str, &OperandPolicy) -> ParseResult<'a>;
+
+/// Parses `opcode(a,b)` with both operands following `policy`.
+///
+/// # Errors
+///
+/// Returns a nom error if `input` does not start with `opcode(a,b)` or an
+/// operand violates `policy`.
+pub fn parse_binary<'a>(
+    opcode: &'static str,
+    input: &'a str,
+    policy: &OperandPolicy,
+) -> ParseResult<'a> {
+    let operand = |input| context("operand", |input| policy.parse(input))(input);
+    let (input, (a, b)) = delimited(
+        preceded(tag(opcode), char('(')),
+        separated_pair(operand, char(','), operand),
+        char(')'),
+    )(input)?;
+    Ok((input, Instruction::new(opcode, &[a, b])))
+}
+
+/// Parses `opcode()`.
+///
+/// # Errors
+///
+/// Returns a nom error if `input` does not start with `opcode()`.
+pub fn parse_nullary<'a>(opcode: &'static str, input: &'a str) -> ParseResult<'a> {
+    value(
+        Instruction::new(opcode, &[]),
+        preceded(tag(opcode), tag("()")),
+    )(input)
+}
+
+/// Parses `mul(a,b)`.
+///
+/// # Errors
+///
+/// See [`parse_binary`].
+pub fn parse_mul<'a>(input: &'a str, policy: &OperandPolicy) -> ParseResult<'a> {
+    parse_binary(Instruction::MUL, input, policy)
+}
+
+/// Parses `do()`.
+///
+/// # Errors
+///
+/// See [`parse_nullary`].
+pub fn parse_do<'a>(input: &'a str, _policy: &OperandPolicy) -> ParseResult<'a> {
+    parse_nullary(Instruction::DO, input)
+}
+
+/// Parses `don't()`.
+///
+/// # Errors
+///
+/// See [`parse_nullary`].
+pub fn parse_dont<'a>(input: &'a str, _policy: &OperandPolicy) -> ParseResult<'a> {
+    parse_nullary(Instruction::DONT, input)
+}
+
+/// Turns corrupted memory into a stream of [`Instruction`]s.
+///
+/// Every parser is registered with the literal prefix its instruction starts
+/// with. Candidate positions are found with a single `memchr` pass over the
+/// first bytes of all prefixes, and only the parsers whose prefix matches there
+/// are tried, in registration order.
+#[derive(Debug, Clone)]
+pub struct Tokenizer {
+    policy: OperandPolicy,
+    parsers: Vec<(&'static str, InstructionParser)>,
+    first_bytes: Vec<u8>,
+    lookahead: usize,
+}
+
+impl Default for Tokenizer {
+    fn default() -> Self {
+        Self::standard(OperandPolicy::default())
+    }
+}
+
+impl Tokenizer {
+    /// A tokenizer without any registered instructions.
+    pub fn new(policy: OperandPolicy) -> Self {
+        Self {
+            policy,
+            parsers: Vec::new(),
+            first_bytes: Vec::new(),
+            lookahead: 0,
+        }
+    }
+
+    /// A tokenizer recognising `mul(a,b)`, `do()` and `don't()`.
+    pub fn standard(policy: OperandPolicy) -> Self {
+        let mut tokenizer = Self::new(policy);
+        tokenizer.register(parse_mul, "mul(", "mul(,)".len() + 2 * policy.max_len());
+        tokenizer.register(parse_do, "do()", "do()".len());
+        tokenizer.register(parse_dont, "don't()", "don't()".len());
+        tokenizer
+    }
+
+    /// Registers `parser` for instructions starting with `prefix` and spanning
+    /// at most `max_len` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prefix` is empty.
+    pub fn register(&mut self, parser: InstructionParser, prefix: &'static str, max_len: usize) {
+        assert!(!prefix.is_empty(), "instruction prefix must not be empty");
+
+        self.parsers.push((prefix, parser));
+        if !self.first_bytes.contains(&prefix.as_bytes()[0]) {
+            self.first_bytes.push(prefix.as_bytes()[0]);
+        }
+        self.lookahead = self.lookahead.max(max_len);
+    }
+
+    /// The maximum number of bytes a single instruction can span.
+    pub const fn lookahead(&self) -> usize {
+        self.lookahead
+    }
+
+    pub fn tokenize(&self, input: &str) -> Vec<Instruction> {
+        self.tokenize_spanned(input)
+            .into_iter()
+            .map(|token| token.instruction)
+            .collect()
+    }
+
+    pub fn tokenize_spanned(&self, input: &str) -> Vec<Token> {
+        self.tokenize_until(input, input.len()).tokens
+    }
+
+    /// Reports every near-miss instruction in `input`.
+    pub fn diagnose(&self, input: &str) -> Vec<Diagnostic> {
+        self.tokenize_until(input, input.len()).diagnostics
+    }
+
+    /// Tokenizes `input`, but only tries instructions starting before `limit`.
+    ///
+    /// The scan stops on a character boundary. Positions where a prefix
+    /// matched but no parser succeeded are recorded as diagnostics and the
+    /// scan resumes at the next character.
+    ///
+    /// # Panics
+    ///
+    /// Never panics: every candidate lies before `limit`, so at least one
+    /// character remains to skip after a near-miss.
+    pub fn tokenize_until(&self, input: &str, limit: usize) -> Scan {
+        let mut limit = limit.min(input.len());
+        while !input.is_char_boundary(limit) {
+            limit += 1;
+        }
+
+        let mut scan = Scan::default();
+        let mut position = 0;
+
+        while position < limit {
+            let Some(start) = self.next_candidate(input.as_bytes(), position) else {
+                break;
+            };
+            if start >= limit {
+                break;
+            }
+
+            let remaining = &input[start..];
+            let mut near_miss: Option<Diagnostic> = None;
+            let parsed = self
+                .parsers
+                .iter()
+                .filter(|(prefix, _)| remaining.starts_with(prefix))
+                .find_map(|(_, parse)| match parse(remaining, &self.policy) {
+                    Ok(parsed) => Some(parsed),
+                    Err(e) => {
+                        let diagnostic = Diagnostic::new(input, start, &e);
+                        if near_miss
+                            .as_ref()
+                            .is_none_or(|other| diagnostic.position > other.position)
+                        {
+                            near_miss = Some(diagnostic);
+                        }
+                        None
+                    }
+                });
+
+            match parsed {
+                Some((next_input, instruction)) => {
+                    position = input.len() - next_input.len();
+                    scan.tokens.push(Token {
+                        instruction,
+                        span: start..position,
+                    });
+                }
+                None => {
+                    scan.diagnostics.extend(near_miss);
+                    let c = remaining.chars().next().expect("candidate is not empty");
+                    position = start + c.len_utf8();
+                }
+            }
+        }
+
+        scan.consumed = position.max(limit);
+        scan
+    }
+
+    fn next_candidate(&self, input: &[u8], from: usize) -> Option<usize> {
+        let haystack = &input[from..];
+        let found = match self.first_bytes.as_slice() {
+            [] => None,
+            [a] => memchr(*a, haystack),
+            [a, b] => memchr2(*a, *b, haystack),
+            [a, b, c] => memchr3(*a, *b, *c, haystack),
+            bytes => haystack.iter().position(|b| bytes.contains(b)),
+        };
+        found.map(|index| from + index)
+    }
+
+    /// Reference implementation trying every parser at every position.
+    pub fn tokenize_naive(&self, input: &str) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        let mut remaining = input;
+
+        while let Some(c) = remaining.chars().next() {
+            match self
+                .parsers
+                .iter()
+                .find_map(|(_, parse)| parse(remaining, &self.policy).ok())
+            {
+                Some((next_input, instruction)) => {
+                    let start = input.len() - remaining.len();
+                    let end = input.len() - next_input.len();
+                    tokens.push(Token {
+                        instruction,
+                        span: start..end,
+                    });
+                    remaining = next_input;
+                }
+                None => remaining = &remaining[c.len_utf8()..],
+            }
+        }
+
+        tokens
+    }
+}
@@ -1,176 +1,108 @@
-use nom::IResult;
-use nom::bytes::complete::tag;
-use nom::character::complete::char;
-use nom::sequence::delimited;
-use std::{error::Error, fs};
+use std::{
+    error::Error,
+    fs::{self, File},
+};
 
+use diagnostic::Diagnostic;
+use instruction::{Instruction, Tokenizer};
+use operand::OperandPolicy;
 use regex::Regex;
+use vm::Vm;
+
+pub mod diagnostic;
+pub mod highlight;
+pub mod instruction;
+pub mod operand;
+pub mod stream;
+pub mod vm;
 
 /// Solves the problem for day 03.
 ///
 /// # Errors
 ///
 /// This function will return an error if the file cannot be read or if the input is invalid.
-#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
 pub fn solve_day03(path: &str) -> Result<(i32, i32), Box<dyn Error>> {
-    let content = fs::read_to_string(path)?;
-
-    let multiplications = extract_multiplications(&content);
-    let part_one = multiplications.iter().map(|x| multiply(*x)).sum::<i32>();
-    let multiplications = parse_multiplications_with_rules(&content);
-    let part_two = multiplications.iter().map(|x| multiply(*x)).sum::<i32>();
-    Ok((part_one, part_two))
-}
-
-fn parse_i32_pair(input: &str) -> IResult<&str, (i32, i32)> {
-    let (input, first) = nom::character::complete::digit1(input)?;
-    let (input, _) = char(',')(input)?;
-    let (input, second) = nom::character::complete::digit1(input)?;
-    Ok((input, (first.parse().unwrap(), second.parse().unwrap())))
-}
-
-fn parse_mul(input: &str) -> IResult<&str, (i32, i32)> {
-    delimited(tag("mul("), parse_i32_pair, char(')'))(input)
+    let (part_one, part_two) = solve_day03_with_policy(path, &OperandPolicy::default())?;
+    Ok((i32::try_from(part_one)?, i32::try_from(part_two)?))
 }
 
-fn ignore_until_mul(input: &str) -> (&str, &str) {
-    let pos = input.find("mul(").unwrap_or(input.len());
-    (&input[pos..], &input[..pos])
-}
-
-fn parse_mul_ignore_prefix(input: &str) -> IResult<&str, (i32, i32)> {
-    let (input, _) = ignore_until_mul(input);
-    parse_mul(input)
+/// Solves the problem for day 03 accepting `mul` operands according to `policy`.
+///
+/// The sums are returned as `i64`, since lenient operands can overflow an `i32`.
+///
+/// # Errors
+///
+/// This function will return an error if the file cannot be read or if the input is invalid.
+pub fn solve_day03_with_policy(
+    path: &str,
+    policy: &OperandPolicy,
+) -> Result<(i64, i64), Box<dyn Error>> {
+    let [part_one, part_two] = stream::run_all(
+        File::open(path)?,
+        &Tokenizer::standard(*policy),
+        [&Vm::unconditional(), &Vm::conditional()],
+        stream::DEFAULT_CHUNK_SIZE,
+    )?;
+    Ok((part_one.sum, part_two.sum))
 }
 
-fn get_index_mul_and_stop(input: &str) -> (Option<usize>, Option<usize>) {
-    let pos_mul = input.find("mul(");
-    let pos_stop = input.find("don't()");
-    (pos_mul, pos_stop)
-}
+/// Renders the memory in `path` with enabled multiplications, disabled ones and
+/// control instructions highlighted.
+///
+/// # Errors
+///
+/// This function will return an error if the file cannot be read.
+pub fn highlight_day03(path: &str, policy: &OperandPolicy) -> Result<String, Box<dyn Error>> {
+    let content = fs::read_to_string(path)?;
 
-fn get_index_start(input: &str) -> Option<usize> {
-    input.find("do()")
+    let tokens = Tokenizer::standard(*policy).tokenize_spanned(&content);
+    let annotated = Vm::conditional().annotate(&tokens);
+    Ok(highlight::highlight(&content, &annotated))
 }
 
-fn parse_multiplications_with_rules(input: &str) -> Vec<(i32, i32)> {
-    let mut results = Vec::new();
-    let mut remaining = input;
-
-    let mut parse = true;
-
-    loop {
-        if parse {
-            let (pos_mul, pos_stop) = get_index_mul_and_stop(remaining);
-            match (pos_mul, pos_stop) {
-                (Some(pos_mul), Some(pos_stop)) => {
-                    if pos_mul < pos_stop {
-                        match parse_mul_ignore_prefix(remaining) {
-                            Ok((next_input, result)) => {
-                                results.push(result);
-                                remaining = next_input;
-                            }
-                            Err(e) => match e {
-                                nom::Err::Error(_) => {
-                                    remaining = &remaining[1..];
-                                    continue;
-                                }
-                                e => {
-                                    eprintln!("Error: {e:?}");
-                                    break;
-                                }
-                            },
-                        }
-                    } else {
-                        parse = false;
-                    }
-                }
-                (Some(_), None) => match parse_mul_ignore_prefix(remaining) {
-                    Ok((next_input, result)) => {
-                        results.push(result);
-                        remaining = next_input;
-                    }
-                    Err(e) => match e {
-                        nom::Err::Error(_) => {
-                            remaining = &remaining[1..];
-                            continue;
-                        }
-                        e => {
-                            dbg!("Error: {:?}", e);
-                            break;
-                        }
-                    },
-                },
-                (None, _) => {
-                    break;
-                }
-            }
-        } else {
-            let pos_start = get_index_start(remaining);
-            match pos_start {
-                Some(pos_start) => {
-                    remaining = &remaining[pos_start..];
-                    parse = true;
-                }
-                None => {
-                    break;
-                }
-            }
-        }
-    }
+/// Lists every near-miss instruction in the memory in `path`.
+///
+/// # Errors
+///
+/// This function will return an error if the file cannot be read.
+pub fn diagnose_day03(
+    path: &str,
+    policy: &OperandPolicy,
+) -> Result<Vec<Diagnostic>, Box<dyn Error>> {
+    let content = fs::read_to_string(path)?;
 
-    results
+    Ok(Tokenizer::standard(*policy).diagnose(&content))
 }
 
 #[allow(dead_code)]
-fn parse_multiplications(input: &str) -> Vec<(i32, i32)> {
-    let mut results = Vec::new();
-    let mut remaining = input;
-
-    loop {
-        match parse_mul_ignore_prefix(remaining) {
-            Ok((next_input, result)) => {
-                results.push(result);
-                remaining = next_input;
-            }
-            Err(e) => match e {
-                nom::Err::Error(_) => {
-                    remaining = &remaining[1..];
-                    continue;
-                }
-                e => {
-                    dbg!("Error: {:?}", e);
-                    break;
-                }
+fn multiplications(instructions: &[Instruction]) -> Vec<(i32, i32)> {
+    instructions
+        .iter()
+        .filter_map(
+            |instruction| match (instruction.opcode, instruction.operands()) {
+                (Instruction::MUL, [a, b]) => Some((*a, *b)),
+                _ => None,
             },
-        }
-
-        // If no 'mul(' is found in the remaining input, break the loop
-        if !remaining.contains("mul(") {
-            break;
-        }
-    }
-
-    results
+        )
+        .collect()
 }
 
 #[allow(dead_code)]
-fn extract_multiplications(input: &str) -> Vec<(i32, i32)> {
-    let re = Regex::new("mul\\(\\d{1,3},\\d{1,3}\\)").unwrap();
-
-    re.captures_iter(input)
-        .filter_map(|cap| cap.get(0))
-        .map(|cap| {
-            // dbg!(&cap.as_str());
-            let m = cap.as_str();
-            let m = m.trim_start_matches("mul(");
-            let m = m.trim_end_matches(')');
-            let (a, b) = m.split_once(',').unwrap();
-            let a: i32 = a.parse().unwrap();
-            let b: i32 = b.parse().unwrap();
-            (a, b)
+fn extract_multiplications(
+    input: &str,
+    policy: &OperandPolicy,
+) -> Result<Vec<(i32, i32)>, regex::Error> {
+    let operand = policy.pattern();
+    let re = Regex::new(&format!("mul\\(({operand}),({operand})\\)"))?;
+
+    Ok(re
+        .captures_iter(input)
+        .filter_map(|cap| {
+            let a = policy.value(cap.get(1)?.as_str())?;
+            let b = policy.value(cap.get(2)?.as_str())?;
+            Some((a, b))
         })
-        .collect()
+        .collect())
 }
 
 #[allow(dead_code)]
@@ -181,11 +113,13 @@ const fn multiply(input: (i32, i32)) -> i32 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use operand::Width;
+    use proptest::prelude::*;
 
     #[test]
     fn test_day03_part_one() {
         let input = "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
-        let result_regex = extract_multiplications(input);
+        let result_regex = extract_multiplications(input, &OperandPolicy::strict()).unwrap();
 
         assert_eq!(4, result_regex.len());
 
@@ -193,19 +127,279 @@ mod tests {
         let got = result_regex.iter().map(|x| multiply(*x)).sum::<i32>();
         assert_eq!(want, got);
 
-        let result = parse_multiplications(input);
+        let instructions = Tokenizer::default().tokenize(input);
+        let result = multiplications(&instructions);
         assert_eq!(4, result.len());
         assert_eq!(result_regex, result);
+
+        assert_eq!(i64::from(want), Vm::unconditional().run(&instructions).sum);
     }
 
     #[test]
     fn test_day03_part_two() {
         let input = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
         let want = 48;
-        let result = parse_multiplications_with_rules(input);
+        let instructions = Tokenizer::default().tokenize(input);
 
-        let got = result.iter().map(|x| multiply(*x)).sum::<i32>();
+        let got = Vm::conditional().run(&instructions).sum;
 
         assert_eq!(want, got);
     }
+
+    #[test]
+    fn test_day03_tokenize() {
+        let input = "xmul(2,4)&don't()mul(3,7)üdo()mul(1,1";
+        let got = Tokenizer::default().tokenize(input);
+        assert_eq!(
+            got,
+            vec![
+                Instruction::mul(2, 4),
+                Instruction::new(Instruction::DONT, &[]),
+                Instruction::mul(3, 7),
+                Instruction::new(Instruction::DO, &[]),
+            ]
+        );
+
+        let mut tokenizer = Tokenizer::new(OperandPolicy::strict());
+        tokenizer.register(instruction::parse_mul, "mul(", 11);
+        assert_eq!(
+            tokenizer.tokenize(input),
+            vec![Instruction::mul(2, 4), Instruction::mul(3, 7)]
+        );
+    }
+
+    #[test]
+    fn test_day03_custom_instruction() {
+        use nom::bytes::complete::tag;
+        use nom::character::complete::char;
+        use nom::sequence::{delimited, tuple};
+
+        fn parse_fma<'a>(input: &'a str, policy: &OperandPolicy) -> instruction::ParseResult<'a> {
+            let operand = |input| policy.parse(input);
+            let (input, (a, _, b, _, c)) = delimited(
+                tag("fma("),
+                tuple((operand, char(','), operand, char(','), operand)),
+                char(')'),
+            )(input)?;
+            Ok((input, Instruction::new("fma", &[a, b, c])))
+        }
+
+        fn fma(state: &mut vm::State, instruction: &Instruction) {
+            if let ("fma", [a, b, c]) = (instruction.opcode, instruction.operands())
+                && state.enabled
+            {
+                state.sum += i64::from(*a) * i64::from(*b) + i64::from(*c);
+            }
+        }
+
+        let mut tokenizer = Tokenizer::default();
+        tokenizer.register(parse_fma, "fma(", "fma(,,)".len() + 3 * 3);
+        let mut vm = Vm::conditional();
+        vm.register(fma);
+
+        let instructions = tokenizer.tokenize("mul(2,3)fma(4,5,6)don't()fma(1,1,1)do()fma(1,2,3)");
+        assert_eq!(instructions[1], Instruction::new("fma", &[4, 5, 6]));
+        assert_eq!(vm.run(&instructions).sum, 6 + 26 + 5);
+
+        let highlighted = highlight::highlight(
+            "fma(1,2,3)",
+            &vm.annotate(&tokenizer.tokenize_spanned("fma(1,2,3)")),
+        );
+        assert_eq!(highlighted, "\x1b[33mfma(1,2,3)\x1b[0m");
+    }
+
+    #[test]
+    fn test_day03_operand_policy() {
+        let input = "mul(1234,5)mul(-2,3)mul(99999999999,1)mul(300,2)mul(12,4)";
+
+        let strict = Tokenizer::standard(OperandPolicy::strict()).tokenize(input);
+        assert_eq!(multiplications(&strict), vec![(300, 2), (12, 4)]);
+
+        let lenient = Tokenizer::standard(OperandPolicy::lenient()).tokenize(input);
+        assert_eq!(
+            multiplications(&lenient),
+            vec![(1234, 5), (-2, 3), (300, 2), (12, 4)]
+        );
+
+        let narrow = OperandPolicy::new(3, false, Width::I8).unwrap();
+        let got = Tokenizer::standard(narrow).tokenize(input);
+        assert_eq!(multiplications(&got), vec![(12, 4)]);
+
+        assert!(OperandPolicy::new(0, false, Width::I32).is_err());
+    }
+
+    fn policy_strategy() -> impl Strategy<Value = OperandPolicy> {
+        (
+            1usize..12,
+            any::<bool>(),
+            prop_oneof![Just(Width::I8), Just(Width::I16), Just(Width::I32)],
+        )
+            .prop_map(|(max_digits, signed, width)| {
+                OperandPolicy::new(max_digits, signed, width).unwrap()
+            })
+    }
+
+    fn memory_strategy() -> impl Strategy<Value = String> {
+        let fragment = prop_oneof![
+            Just("mul(".to_string()),
+            Just("do()".to_string()),
+            Just("don't()".to_string()),
+            "[0-9]{1,12}",
+            "[-,()mul ]",
+        ];
+        proptest::collection::vec(fragment, 0..40).prop_map(|fragments| fragments.concat())
+    }
+
+    proptest! {
+        #[test]
+        fn test_day03_regex_and_nom_agree(
+            memory in memory_strategy(),
+            policy in policy_strategy(),
+        ) {
+            let instructions = Tokenizer::standard(policy).tokenize(&memory);
+            prop_assert_eq!(
+                extract_multiplications(&memory, &policy).unwrap(),
+                multiplications(&instructions)
+            );
+        }
+    }
+
+    #[test]
+    fn test_day03_spans() {
+        let input = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+        let tokens = Tokenizer::default().tokenize_spanned(input);
+        let annotated = Vm::conditional().annotate(&tokens);
+
+        let got: Vec<(&str, bool)> = annotated
+            .iter()
+            .map(|a| (&input[a.token.span.clone()], a.enabled))
+            .collect();
+        assert_eq!(
+            got,
+            vec![
+                ("mul(2,4)", true),
+                ("don't()", false),
+                ("mul(5,5)", false),
+                ("mul(11,8)", false),
+                ("do()", true),
+                ("mul(8,5)", true),
+            ]
+        );
+
+        let highlighted = highlight::highlight(
+            "amul(1,2)don't()mul(3,4)b",
+            &Vm::conditional()
+                .annotate(&Tokenizer::default().tokenize_spanned("amul(1,2)don't()mul(3,4)b")),
+        );
+        assert_eq!(
+            highlighted,
+            "a\x1b[32mmul(1,2)\x1b[0m\x1b[33mdon't()\x1b[0m\x1b[31mmul(3,4)\x1b[0mb"
+        );
+    }
+
+    struct Trickle<'a> {
+        data: &'a [u8],
+        step: usize,
+    }
+
+    impl std::io::Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = self.step.min(buf.len()).min(self.data.len());
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn test_day03_stream_chunk_boundaries() {
+        let input = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))äöümul(123,456)don't()do()mul(1,1)";
+        let tokenizer = Tokenizer::default();
+        let instructions = tokenizer.tokenize(input);
+
+        for vm in [Vm::unconditional(), Vm::conditional()] {
+            let want = vm.run(&instructions);
+            for chunk_size in 1..=input.len() + 1 {
+                let got = stream::run(input.as_bytes(), &tokenizer, &vm, chunk_size).unwrap();
+                assert_eq!(want, got, "chunk size {chunk_size}");
+
+                let reader = Trickle {
+                    data: input.as_bytes(),
+                    step: 3,
+                };
+                let got = stream::run(reader, &tokenizer, &vm, chunk_size).unwrap();
+                assert_eq!(want, got, "trickled with chunk size {chunk_size}");
+            }
+        }
+
+        let want = [
+            Vm::unconditional().run(&instructions),
+            Vm::conditional().run(&instructions),
+        ];
+        for chunk_size in 1..=input.len() + 1 {
+            let reader = Trickle {
+                data: input.as_bytes(),
+                step: 3,
+            };
+            let vms = [&Vm::unconditional(), &Vm::conditional()];
+            let got = stream::run_all(reader, &tokenizer, vms, chunk_size).unwrap();
+            assert_eq!(want, got, "both machines with chunk size {chunk_size}");
+        }
+    }
+
+    #[test]
+    fn test_day03_stream_invalid_utf8() {
+        let input = b"mul(2,4)\xffmul(1,1)";
+        let got = stream::run(&input[..], &Tokenizer::default(), &Vm::conditional(), 4);
+        assert!(got.is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn test_day03_search_matches_naive(memory in memory_strategy(), policy in policy_strategy()) {
+            let tokenizer = Tokenizer::standard(policy);
+            prop_assert_eq!(
+                tokenizer.tokenize_naive(&memory),
+                tokenizer.tokenize_spanned(&memory)
+            );
+        }
+    }
+
+    #[test]
+    fn test_day03_diagnostics() {
+        let input = "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))mul(1234,1)mul(";
+        let got = Tokenizer::default().diagnose(input);
+
+        assert_eq!(
+            got,
+            vec![
+                Diagnostic {
+                    position: 47,
+                    expected: "')'".to_string(),
+                    found: Some(']'),
+                    partial: "mul(32,64".to_string(),
+                },
+                Diagnostic {
+                    position: 78,
+                    expected: "','".to_string(),
+                    found: Some('4'),
+                    partial: "mul(123".to_string(),
+                },
+                Diagnostic {
+                    position: 86,
+                    expected: "operand".to_string(),
+                    found: None,
+                    partial: "mul(".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            got[0].to_string(),
+            "byte 47: expected ')', found ']' after `mul(32,64`"
+        );
+        assert_eq!(
+            got[2].to_string(),
+            "byte 86: expected operand, found end of input after `mul(`"
+        );
+    }
 }
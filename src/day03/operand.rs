@@ -0,0 +1,142 @@
+use std::error::Error;
+
+use nom::IResult;
+use nom::bytes::complete::take_while_m_n;
+use nom::character::complete::char;
+use nom::combinator::{map_opt, opt, recognize};
+use nom::error::VerboseError;
+use nom::sequence::pair;
+
+/// The integer type an operand has to fit into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Width {
+    I8,
+    I16,
+    I32,
+}
+
+impl Width {
+    const fn range(self) -> (i64, i64) {
+        match self {
+            Self::I8 => (i8::MIN as i64, i8::MAX as i64),
+            Self::I16 => (i16::MIN as i64, i16::MAX as i64),
+            Self::I32 => (i32::MIN as i64, i32::MAX as i64),
+        }
+    }
+}
+
+/// Rules an operand of `mul(a,b)` has to follow to be accepted.
+///
+/// Every Day 03 parser, regex or nom based, honours the same policy so they
+/// agree on which instructions are valid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OperandPolicy {
+    max_digits: usize,
+    signed: bool,
+    width: Width,
+}
+
+impl Default for OperandPolicy {
+    fn default() -> Self {
+        Self::strict()
+    }
+}
+
+impl OperandPolicy {
+    /// The puzzle rules: one to three digits without a sign.
+    pub const fn strict() -> Self {
+        Self {
+            max_digits: 3,
+            signed: false,
+            width: Width::I32,
+        }
+    }
+
+    /// Accepts optionally negative operands of up to 10 digits that fit into an `i32`.
+    ///
+    /// Longer, zero-padded operands are still rejected, so an instruction stays
+    /// short enough for the streaming scanner's lookahead.
+    pub const fn lenient() -> Self {
+        Self {
+            max_digits: 10,
+            signed: true,
+            width: Width::I32,
+        }
+    }
+
+    /// Creates a new policy.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `max_digits` is 0.
+    pub fn new(max_digits: usize, signed: bool, width: Width) -> Result<Self, Box<dyn Error>> {
+        if max_digits == 0 {
+            return Err(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Invalid operand policy. Operands must allow at least one digit",
+            )));
+        }
+
+        Ok(Self {
+            max_digits,
+            signed,
+            width,
+        })
+    }
+
+    pub const fn max_digits(&self) -> usize {
+        self.max_digits
+    }
+
+    /// Whether operands may start with a minus sign.
+    pub const fn signed(&self) -> bool {
+        self.signed
+    }
+
+    pub const fn width(&self) -> Width {
+        self.width
+    }
+
+    /// The maximum number of bytes a single operand can span.
+    pub const fn max_len(&self) -> usize {
+        self.max_digits + if self.signed { 1 } else { 0 }
+    }
+
+    /// A regex fragment matching a single operand under this policy.
+    pub fn pattern(&self) -> String {
+        let sign = if self.signed { "-?" } else { "" };
+        format!("{sign}\\d{{1,{}}}", self.max_digits)
+    }
+
+    /// Converts the text of an operand matched by [`Self::pattern`] or
+    /// [`Self::parse`] into its value, if it fits into the configured width.
+    pub fn value(&self, text: &str) -> Option<i32> {
+        let (min, max) = self.width.range();
+        let value: i64 = text.parse().ok()?;
+        if (min..=max).contains(&value) {
+            i32::try_from(value).ok()
+        } else {
+            None
+        }
+    }
+
+    /// Parses an operand following the policy.
+    ///
+    /// # Errors
+    ///
+    /// Returns a nom error if `input` does not start with a digit, optionally
+    /// preceded by `-` for a signed policy, or if the value of its first
+    /// `max_digits` digits does not fit into the configured width.
+    pub fn parse<'a>(&self, input: &'a str) -> IResult<&'a str, i32, VerboseError<&'a str>> {
+        let sign = |input: &'a str| {
+            if self.signed {
+                opt(char('-'))(input)
+            } else {
+                Ok((input, None))
+            }
+        };
+        let digits = take_while_m_n(1, self.max_digits, |c: char| c.is_ascii_digit());
+
+        map_opt(recognize(pair(sign, digits)), |text| self.value(text))(input)
+    }
+}
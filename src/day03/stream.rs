@@ -0,0 +1,83 @@
+use std::io::{self, Read};
+
+use super::instruction::Tokenizer;
+use super::vm::{State, Vm};
+
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Runs `vm` over memory read from `reader` in chunks of `chunk_size` bytes.
+///
+/// Only the last `tokenizer.lookahead() - 1` bytes of a chunk (plus a possibly
+/// incomplete UTF-8 sequence) are carried over into the next one, so
+/// instructions split across chunk boundaries are still recognised while the
+/// memory usage stays constant.
+///
+/// # Errors
+///
+/// This function will return an error if reading fails or the input is not valid UTF-8.
+pub fn run<R: Read>(
+    reader: R,
+    tokenizer: &Tokenizer,
+    vm: &Vm,
+    chunk_size: usize,
+) -> io::Result<State> {
+    let [state] = run_all(reader, tokenizer, [vm], chunk_size)?;
+    Ok(state)
+}
+
+/// Like [`run`], but feeds every chunk to all of `vms`, so the input is read
+/// only once.
+///
+/// # Errors
+///
+/// This function will return an error if reading fails or the input is not valid UTF-8.
+///
+/// # Panics
+///
+/// Never panics: a chunk ending inside a UTF-8 sequence is only cut back to
+/// its valid prefix.
+pub fn run_all<R: Read, const N: usize>(
+    mut reader: R,
+    tokenizer: &Tokenizer,
+    vms: [&Vm; N],
+    chunk_size: usize,
+) -> io::Result<[State; N]> {
+    let mut states = std::array::from_fn(|_| State::default());
+    let mut chunk = vec![0; chunk_size.max(1)];
+    let mut buffer = Vec::with_capacity(chunk.len() + tokenizer.lookahead());
+
+    loop {
+        let read = match reader.read(&mut chunk) {
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        let eof = read == 0;
+        buffer.extend_from_slice(&chunk[..read]);
+
+        let text = match std::str::from_utf8(&buffer) {
+            Ok(text) => text,
+            Err(e) if e.error_len().is_none() && !eof => {
+                std::str::from_utf8(&buffer[..e.valid_up_to()]).expect("prefix is valid")
+            }
+            Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+        };
+
+        let limit = if eof {
+            text.len()
+        } else {
+            (text.len() + 1).saturating_sub(tokenizer.lookahead())
+        };
+        let scan = tokenizer.tokenize_until(text, limit);
+        for (vm, state) in vms.iter().zip(&mut states) {
+            for token in &scan.tokens {
+                vm.step(state, &token.instruction);
+            }
+        }
+        buffer.drain(..scan.consumed);
+
+        if eof {
+            return Ok(states);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use super::instruction::{Instruction, Token};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct State {
+    pub enabled: bool,
+    pub sum: i64,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            sum: 0,
+        }
+    }
+}
+
+/// A [`Token`] together with whether the machine was enabled after executing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotated {
+    pub token: Token,
+    pub enabled: bool,
+}
+
+pub type Rule = fn(&mut State, &Instruction);
+
+/// Evaluates a stream of [`Instruction`]s.
+///
+/// Every registered rule sees every instruction and may update the [`State`].
+/// Rules ignore opcodes they do not handle, so instructions can be added
+/// without touching the existing ones.
+#[derive(Debug, Clone, Default)]
+pub struct Vm {
+    rules: Vec<Rule>,
+}
+
+impl Vm {
+    /// A machine that only evaluates multiplications and ignores `do()`/`don't()`.
+    pub fn unconditional() -> Self {
+        let mut vm = Self::default();
+        vm.register(multiply);
+        vm
+    }
+
+    /// A machine that honours `do()`/`don't()` before evaluating multiplications.
+    pub fn conditional() -> Self {
+        let mut vm = Self::default();
+        vm.register(toggle);
+        vm.register(multiply);
+        vm
+    }
+
+    pub fn register(&mut self, rule: Rule) {
+        self.rules.push(rule);
+    }
+
+    pub fn run(&self, instructions: &[Instruction]) -> State {
+        let mut state = State::default();
+        for instruction in instructions {
+            self.step(&mut state, instruction);
+        }
+        state
+    }
+
+    pub fn step(&self, state: &mut State, instruction: &Instruction) {
+        for rule in &self.rules {
+            rule(state, instruction);
+        }
+    }
+
+    pub fn annotate(&self, tokens: &[Token]) -> Vec<Annotated> {
+        let mut state = State::default();
+        tokens
+            .iter()
+            .map(|token| {
+                self.step(&mut state, &token.instruction);
+                Annotated {
+                    token: token.clone(),
+                    enabled: state.enabled,
+                }
+            })
+            .collect()
+    }
+}
+
+pub fn multiply(state: &mut State, instruction: &Instruction) {
+    if let (Instruction::MUL, [a, b]) = (instruction.opcode, instruction.operands())
+        && state.enabled
+    {
+        state.sum += i64::from(*a) * i64::from(*b);
+    }
+}
+
+pub fn toggle(state: &mut State, instruction: &Instruction) {
+    match instruction.opcode {
+        Instruction::DO => state.enabled = true,
+        Instruction::DONT => state.enabled = false,
+        _ => (),
+    }
+}
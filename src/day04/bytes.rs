@@ -0,0 +1,260 @@
+use std::{error::Error, fmt};
+
+use super::grid::ShapeError;
+use crate::parse::grid_lines;
+
+/// Input that cannot be parsed into a [`ByteGrid`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ByteGridError {
+    /// A character outside of ASCII, which would not fit into a single byte cell.
+    NotAscii {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+    Shape(ShapeError),
+}
+
+impl fmt::Display for ByteGridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAscii {
+                line,
+                column,
+                found,
+            } => write!(
+                f,
+                "Invalid grid. Line {line}, column {column} contains the non-ASCII character '{found}'"
+            ),
+            Self::Shape(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for ByteGridError {}
+
+impl From<ShapeError> for ByteGridError {
+    fn from(error: ShapeError) -> Self {
+        Self::Shape(error)
+    }
+}
+
+/// A rectangular grid of ASCII bytes stored row-major in one contiguous buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ByteGrid {
+    cells: Vec<u8>,
+    width: usize,
+    height: usize,
+}
+
+/// Every row, column, diagonal and anti-diagonal of a [`ByteGrid`], each
+/// family copied into one contiguous buffer with lines separated by `0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineScans {
+    families: [Vec<u8>; 4],
+}
+
+const SEPARATOR: u8 = 0;
+
+impl ByteGrid {
+    /// Parses the [`grid_lines`] of `input` into a rectangular grid.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the input contains a non-ASCII
+    /// character or a row differs in length from the first one.
+    pub fn parse(input: &str) -> Result<Self, ByteGridError> {
+        let lines = grid_lines(input);
+        for line in &lines {
+            if let Some((column, found)) =
+                line.text.chars().enumerate().find(|(_, c)| !c.is_ascii())
+            {
+                return Err(ByteGridError::NotAscii {
+                    line: line.number,
+                    column: column + 1,
+                    found,
+                });
+            }
+        }
+
+        let width = lines.first().map_or(0, |line| line.text.len());
+        let mut cells = Vec::with_capacity(width * lines.len());
+        for line in &lines {
+            if line.text.len() != width {
+                return Err(ShapeError {
+                    line: line.number,
+                    expected: width,
+                    found: line.text.len(),
+                }
+                .into());
+            }
+            cells.extend_from_slice(line.text.as_bytes());
+        }
+
+        Ok(Self {
+            cells,
+            width,
+            height: lines.len(),
+        })
+    }
+
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> Option<u8> {
+        (row < self.height && column < self.width).then(|| self.cells[row * self.width + column])
+    }
+
+    /// Copies every line of the grid into contiguous buffers.
+    ///
+    /// The grid is traversed in square tiles, so both reads and the scattered
+    /// writes into the column and diagonal buffers stay mostly in cache.
+    pub fn line_scans(&self) -> LineScans {
+        const TILE: usize = 64;
+        let (width, height) = (self.width, self.height);
+        if width == 0 {
+            return LineScans {
+                families: Default::default(),
+            };
+        }
+
+        let diagonal_count = width + height - 1;
+        let line_offsets = |len: &dyn Fn(usize) -> usize| {
+            let mut offsets = Vec::with_capacity(diagonal_count);
+            let mut offset = 0;
+            for line in 0..diagonal_count {
+                offsets.push(offset);
+                offset += len(line) + 1;
+            }
+            (offsets, offset)
+        };
+        // Diagonal `k` starts at (0, width - 1 - k) or (k - width + 1, 0).
+        let (diagonal_offsets, diagonal_len) = line_offsets(&|k| {
+            let (row, column) = (k.saturating_sub(width - 1), (width - 1).saturating_sub(k));
+            (height - row).min(width - column)
+        });
+        // Anti-diagonal `k` starts at (0, k) or (k - width + 1, width - 1).
+        let (anti_diagonal_offsets, anti_diagonal_len) = line_offsets(&|k| {
+            let (row, column) = (k.saturating_sub(width - 1), k.min(width - 1));
+            (height - row).min(column + 1)
+        });
+
+        let mut rows = Vec::with_capacity((width + 1) * height);
+        for row in self.cells.chunks_exact(width) {
+            rows.extend_from_slice(row);
+            rows.push(SEPARATOR);
+        }
+
+        let mut columns = vec![SEPARATOR; (height + 1) * width];
+        let mut diagonals = vec![SEPARATOR; diagonal_len];
+        let mut anti_diagonals = vec![SEPARATOR; anti_diagonal_len];
+
+        for tile_row in (0..height).step_by(TILE) {
+            for tile_column in (0..width).step_by(TILE) {
+                for row in tile_row..(tile_row + TILE).min(height) {
+                    for column in tile_column..(tile_column + TILE).min(width) {
+                        let cell = self.cells[row * width + column];
+                        columns[column * (height + 1) + row] = cell;
+                        diagonals[diagonal_offsets[width - 1 - column + row] + row.min(column)] =
+                            cell;
+                        let k = row + column;
+                        anti_diagonals
+                            [anti_diagonal_offsets[k] + row - k.saturating_sub(width - 1)] = cell;
+                    }
+                }
+            }
+        }
+
+        LineScans {
+            families: [rows, columns, diagonals, anti_diagonals],
+        }
+    }
+
+    /// Counts the X-MAS crosses centred on every interior `A`.
+    pub fn count_cross(&self) -> usize {
+        let width = self.width;
+        if width < 3 || self.height < 3 {
+            return 0;
+        }
+
+        let mut count = 0;
+        for row in 1..self.height - 1 {
+            let line = &self.cells[row * width..(row + 1) * width];
+            for column in memchr::memchr_iter(b'A', &line[1..width - 1]) {
+                let center = row * width + column + 1;
+                let diagonal = [
+                    self.cells[center - width - 1],
+                    self.cells[center + width + 1],
+                ];
+                let anti_diagonal = [
+                    self.cells[center - width + 1],
+                    self.cells[center + width - 1],
+                ];
+                if is_ms(diagonal) && is_ms(anti_diagonal) {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+}
+
+const fn is_ms(pair: [u8; 2]) -> bool {
+    matches!(pair, [b'M', b'S'] | [b'S', b'M'])
+}
+
+impl LineScans {
+    /// Counts the occurrences of `word` in all eight directions, i.e. forwards
+    /// and backwards along every line.
+    pub fn count_word(&self, word: &[u8]) -> usize {
+        if word.is_empty() {
+            return 0;
+        }
+        let reversed: Vec<u8> = word.iter().rev().copied().collect();
+
+        self.families
+            .iter()
+            .map(|line| occurrences(line, word, &reversed))
+            .sum()
+    }
+}
+
+/// Counts the possibly overlapping occurrences of `word` and `reversed` in `line`.
+///
+/// Words of up to eight bytes are compared as a rolling `u64` window, which
+/// avoids a data-dependent branch per position.
+fn occurrences(line: &[u8], word: &[u8], reversed: &[u8]) -> usize {
+    if word.len() > 8 {
+        let Some(last_start) = line.len().checked_sub(word.len()) else {
+            return 0;
+        };
+        let matches = |start: usize, word: &[u8]| {
+            word.iter()
+                .zip(&line[start..])
+                .all(|(expected, found)| expected == found)
+        };
+        return (0..=last_start)
+            .map(|start| usize::from(matches(start, word)) + usize::from(matches(start, reversed)))
+            .sum();
+    }
+
+    let mask = u64::MAX >> (64 - 8 * word.len());
+    let pack = |word: &[u8]| {
+        word.iter()
+            .fold(0u64, |window, byte| (window << 8) | u64::from(*byte))
+    };
+    let (forward, backward) = (pack(word), pack(reversed));
+
+    let mut window = 0u64;
+    let mut count = 0;
+    for byte in line {
+        window = ((window << 8) | u64::from(*byte)) & mask;
+        count += usize::from(window == forward) + usize::from(window == backward);
+    }
+    count
+}
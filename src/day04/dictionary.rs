@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+
+use super::search::{Direction, WordMatch};
+use crate::grid::Grid;
+
+/// A set of words stored as a trie, so all of them can be searched in a
+/// single walk from every cell and direction of a grid.
+#[derive(Debug, Clone)]
+pub struct Dictionary {
+    nodes: Vec<Node>,
+    words: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    children: HashMap<char, usize>,
+    word: Option<usize>,
+}
+
+/// A dictionary word found in a grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FoundWord<'a> {
+    pub word: &'a str,
+    pub location: WordMatch,
+}
+
+impl Dictionary {
+    pub fn new<I, S>(words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut dictionary = Self {
+            nodes: vec![Node::default()],
+            words: Vec::new(),
+        };
+        for word in words {
+            dictionary.insert(word.as_ref());
+        }
+        dictionary
+    }
+
+    /// Parses a dictionary with one word per line, ignoring blank lines.
+    pub fn parse(input: &str) -> Self {
+        Self::new(input.lines().map(str::trim).filter(|word| !word.is_empty()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    fn insert(&mut self, word: &str) {
+        let mut node = 0;
+        for c in word.chars() {
+            node = match self.nodes[node].children.get(&c) {
+                Some(&child) => child,
+                None => {
+                    self.nodes.push(Node::default());
+                    let child = self.nodes.len() - 1;
+                    self.nodes[node].children.insert(c, child);
+                    child
+                }
+            };
+        }
+
+        if node != 0 && self.nodes[node].word.is_none() {
+            self.nodes[node].word = Some(self.words.len());
+            self.words.push(word.to_string());
+        }
+    }
+
+    /// Finds every dictionary word in `grid` in all eight directions.
+    ///
+    /// Single-character words are reported once per cell, without repeating
+    /// them for every direction.
+    pub fn solve(&self, grid: &Grid<char>) -> Vec<FoundWord<'_>> {
+        let mut found = Vec::new();
+
+        for row in 0..grid.height() {
+            for column in 0..grid.width() {
+                for (index, &direction) in Direction::ALL.iter().enumerate() {
+                    let start = WordMatch {
+                        row,
+                        column,
+                        direction,
+                    };
+                    self.walk(grid, start, index == 0, &mut found);
+                }
+            }
+        }
+
+        found
+    }
+
+    fn walk<'a>(
+        &'a self,
+        grid: &Grid<char>,
+        start: WordMatch,
+        single: bool,
+        found: &mut Vec<FoundWord<'a>>,
+    ) {
+        let mut node = 0;
+        for length in 0.. {
+            let Some(c) = grid.get(start.cell(length)) else {
+                return;
+            };
+            let Some(&child) = self.nodes[node].children.get(c) else {
+                return;
+            };
+            node = child;
+
+            if let Some(word) = self.nodes[node].word
+                && (length > 0 || single)
+            {
+                found.push(FoundWord {
+                    word: &self.words[word],
+                    location: start,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,265 @@
+use std::{error::Error, fmt};
+
+use super::search::{Direction, WordMatch, find_word};
+use super::stencil::find_stencil;
+use crate::geometry::Point;
+use crate::grid::Grid;
+
+/// Describes a word search grid to generate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PuzzleSpec {
+    pub width: usize,
+    pub height: usize,
+    pub word: String,
+    /// Exact number of occurrences of `word` in all eight directions.
+    pub count: usize,
+    /// Exact number of X-MAS crosses, if they should be controlled as well.
+    pub crosses: Option<usize>,
+    pub seed: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GenerateError {
+    EmptyWord,
+    /// More occurrences were requested than there are positions for the word.
+    TooManyWords {
+        count: usize,
+        positions: usize,
+    },
+    /// The word reads the same in several directions, so every placement is
+    /// found `multiple` times and `count` is not a multiple of it.
+    UnreachableCount {
+        count: usize,
+        multiple: usize,
+    },
+    /// More crosses were requested than fit into the grid.
+    TooManyCrosses {
+        crosses: usize,
+        positions: usize,
+    },
+    /// No grid satisfying the spec was found within the iteration budget.
+    Unsatisfiable,
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyWord => write!(f, "Invalid puzzle. The word must not be empty"),
+            Self::TooManyWords { count, positions } => write!(
+                f,
+                "Invalid puzzle. {count} occurrences requested, but the word fits in only {positions} positions"
+            ),
+            Self::UnreachableCount { count, multiple } => write!(
+                f,
+                "Invalid puzzle. Every placement of the word is found {multiple} times, \
+                 so a count of {count} is impossible"
+            ),
+            Self::TooManyCrosses { crosses, positions } => write!(
+                f,
+                "Invalid puzzle. {crosses} crosses requested, but only {positions} fit into the grid"
+            ),
+            Self::Unsatisfiable => write!(
+                f,
+                "Unable to generate a grid with the requested number of occurrences"
+            ),
+        }
+    }
+}
+
+impl Error for GenerateError {}
+
+/// A small, seedable `SplitMix64` generator so puzzles are reproducible.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        items[self.below(items.len())]
+    }
+}
+
+/// Generates a grid in which `spec.word` occurs exactly `spec.count` times
+/// and, if requested, exactly `spec.crosses` X-MAS crosses appear.
+///
+/// The grid is filled with letters of the word (and of `MAS` for crosses)
+/// plus filler letters that occur in neither, then repaired by placing missing
+/// occurrences and breaking surplus ones until the existing matchers report
+/// the requested counts.
+///
+/// # Errors
+///
+/// This function will return an error if the word is empty, the requested
+/// counts cannot be reached in a grid of this size, or no such grid could be
+/// found within the iteration budget.
+#[allow(clippy::cast_possible_wrap)]
+pub fn generate(spec: &PuzzleSpec) -> Result<Grid<char>, GenerateError> {
+    let word: Vec<char> = spec.word.chars().collect();
+    check(spec, &word)?;
+
+    let mut alphabet = word.clone();
+    if spec.crosses.is_some() {
+        alphabet.extend(['M', 'A', 'S']);
+    }
+    let filler: Vec<char> = ('A'..='Z')
+        .chain(['.'])
+        .filter(|c| !alphabet.contains(c))
+        .collect();
+    alphabet.extend(filler);
+    alphabet.sort_unstable();
+    alphabet.dedup();
+
+    let mut rng = Rng(spec.seed);
+    let mut grid = Grid::filled(spec.width, spec.height, ' ').map(|_| rng.pick(&alphabet));
+
+    let cross = super::cross_stencil();
+    let budget = 1000 + 100 * spec.width * spec.height;
+
+    for _ in 0..budget {
+        let words = find_word(&grid, &spec.word, &Direction::ALL);
+        let crosses = find_stencil(&grid, &cross);
+        let crosses_done = spec.crosses.is_none_or(|want| crosses.len() == want);
+
+        if words.len() == spec.count && crosses_done {
+            return Ok(grid);
+        }
+
+        if words.len() > spec.count {
+            let found = rng.pick(&words);
+            let cell = found.cell(rng.below(word.len()));
+            mutate(&mut grid, cell, &alphabet, &mut rng);
+        } else if words.len() < spec.count {
+            place(&mut grid, &word, &mut rng);
+        }
+
+        if let Some(want) = spec.crosses {
+            if crosses.len() > want {
+                let found = &crosses[rng.below(crosses.len())];
+                let cell = Point::new(found.column as isize + 1, found.row as isize + 1);
+                mutate(&mut grid, cell, &alphabet, &mut rng);
+            } else if crosses.len() < want {
+                place_cross(&mut grid, &mut rng);
+            }
+        }
+    }
+
+    Err(GenerateError::Unsatisfiable)
+}
+
+/// Rejects specs no grid can satisfy before spending the iteration budget.
+fn check(spec: &PuzzleSpec, word: &[char]) -> Result<(), GenerateError> {
+    if word.is_empty() {
+        return Err(GenerateError::EmptyWord);
+    }
+
+    // The matcher reports a placement once for every direction it reads the
+    // same in: all eight for a single letter, both ways for a palindrome.
+    let multiple = if word.len() == 1 {
+        Direction::ALL.len()
+    } else if word.iter().eq(word.iter().rev()) {
+        2
+    } else {
+        1
+    };
+    if !spec.count.is_multiple_of(multiple) {
+        return Err(GenerateError::UnreachableCount {
+            count: spec.count,
+            multiple,
+        });
+    }
+
+    let grid = Grid::filled(spec.width, spec.height, ());
+    let positions = (0..spec.height)
+        .flat_map(|row| (0..spec.width).map(move |column| (row, column)))
+        .flat_map(|(row, column)| {
+            Direction::ALL.map(|direction| WordMatch {
+                row,
+                column,
+                direction,
+            })
+        })
+        .filter(|start| grid.contains(start.cell(word.len() - 1)))
+        .count();
+    if spec.count > positions {
+        return Err(GenerateError::TooManyWords {
+            count: spec.count,
+            positions,
+        });
+    }
+
+    let cross_positions = spec.width.saturating_sub(2) * spec.height.saturating_sub(2);
+    if let Some(crosses) = spec.crosses
+        && crosses > cross_positions
+    {
+        return Err(GenerateError::TooManyCrosses {
+            crosses,
+            positions: cross_positions,
+        });
+    }
+
+    Ok(())
+}
+
+fn mutate(grid: &mut Grid<char>, cell: Point, alphabet: &[char], rng: &mut Rng) {
+    let current = grid[cell];
+    let others: Vec<char> = alphabet.iter().copied().filter(|c| *c != current).collect();
+    if !others.is_empty() {
+        grid[cell] = rng.pick(&others);
+    }
+}
+
+fn place(grid: &mut Grid<char>, word: &[char], rng: &mut Rng) {
+    let (height, width) = (grid.height(), grid.width());
+    if height == 0 || width == 0 {
+        return;
+    }
+
+    let start = WordMatch {
+        row: rng.below(height),
+        column: rng.below(width),
+        direction: rng.pick(&Direction::ALL),
+    };
+    let cells: Vec<Point> = (0..word.len()).map(|index| start.cell(index)).collect();
+    if cells.iter().all(|cell| grid.contains(*cell)) {
+        for (cell, c) in cells.into_iter().zip(word) {
+            grid[cell] = *c;
+        }
+    }
+}
+
+#[allow(clippy::cast_possible_wrap)]
+fn place_cross(grid: &mut Grid<char>, rng: &mut Rng) {
+    let (height, width) = (grid.height(), grid.width());
+    if height < 3 || width < 3 {
+        return;
+    }
+
+    let (row, column) = (rng.below(height - 2), rng.below(width - 2));
+    let corner = Point::new(column as isize, row as isize);
+    let [a, b] = if rng.below(2) == 0 {
+        ['M', 'S']
+    } else {
+        ['S', 'M']
+    };
+    let [c, d] = if rng.below(2) == 0 {
+        ['M', 'S']
+    } else {
+        ['S', 'M']
+    };
+    grid[corner] = a;
+    grid[corner.offset(2, 2)] = b;
+    grid[corner.offset(2, 0)] = c;
+    grid[corner.offset(0, 2)] = d;
+    grid[corner.offset(1, 1)] = 'A';
+}
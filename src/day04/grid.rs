@@ -0,0 +1,55 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+pub use crate::grid::{Grid, ShapeError};
+use crate::parse::grid_lines;
+
+/// How rows of different lengths are handled when parsing a grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    /// Every row must be as long as the first one.
+    Rectangular,
+    /// Rows may differ in length, missing cells are filled with the empty
+    /// value of the cell type, i.e. `'\0'` or an empty string.
+    Ragged,
+}
+
+/// Parses the [`grid_lines`] of `input` into a grid of characters.
+///
+/// # Errors
+///
+/// This function will return an error if `shape` is [`Shape::Rectangular`] and a row differs in length.
+pub fn parse_chars(input: &str, shape: Shape) -> Result<Grid<char>, ShapeError> {
+    parse_with(input, shape, |line| line.chars().collect())
+}
+
+/// Parses the [`grid_lines`] of `input` into a grid of extended grapheme
+/// clusters, so a letter with combining marks or an emoji sequence occupies a
+/// single cell.
+///
+/// # Errors
+///
+/// This function will return an error if `shape` is [`Shape::Rectangular`] and a row differs in length.
+pub fn parse_graphemes(input: &str, shape: Shape) -> Result<Grid<String>, ShapeError> {
+    parse_with(input, shape, graphemes)
+}
+
+/// Splits `word` into extended grapheme clusters.
+pub fn graphemes(word: &str) -> Vec<String> {
+    word.graphemes(true).map(str::to_string).collect()
+}
+
+fn parse_with<T: Default>(
+    input: &str,
+    shape: Shape,
+    cells: impl Fn(&str) -> Vec<T>,
+) -> Result<Grid<T>, ShapeError> {
+    let lines = grid_lines(input);
+    let rows: Vec<Vec<T>> = lines.iter().map(|line| cells(line.text)).collect();
+    match shape {
+        Shape::Rectangular => Grid::from_rows(rows).map_err(|error| ShapeError {
+            line: lines[error.line - 1].number,
+            ..error
+        }),
+        Shape::Ragged => Ok(Grid::from_ragged_rows(rows)),
+    }
+}
@@ -1,5 +1,17 @@
 use std::{error::Error, fs};
 
+use dictionary::Dictionary;
+use grid::{Grid, Shape, parse_chars};
+use search::{Direction, find_word};
+use stencil::{Stencil, find_stencil};
+
+pub mod bytes;
+pub mod dictionary;
+pub mod generator;
+pub mod grid;
+pub mod search;
+pub mod stencil;
+
 /// Solves the problem for day 04.
 ///
 /// # Errors
@@ -9,126 +21,69 @@ use std::{error::Error, fs};
 pub fn solve_day04(path: &str) -> Result<(i32, i32), Box<dyn Error>> {
     let content = fs::read_to_string(path)?;
 
-    let data = string_to_arrays(&content);
+    let data = parse_chars(&content, Shape::Rectangular)?;
 
     let part_one = count_word(&data);
     let part_two = count_cross(&data);
     Ok((part_one, part_two))
 }
 
-#[allow(dead_code)]
-fn string_to_arrays(input: &str) -> Vec<Vec<char>> {
-    input
-        .split_terminator('\n')
-        .map(|x| x.chars().collect())
-        .collect()
-}
-
-#[allow(dead_code)]
-fn count_cross(input: &[Vec<char>]) -> i32 {
-    const CROSS: [char; 3] = ['M', 'A', 'S'];
-    let mut count = 0;
-
-    for (li, line) in input.iter().enumerate() {
-        for (ci, c) in line.iter().enumerate() {
-            if c != &CROSS[1] {
-                continue;
-            }
-
-            if ci > 0 && ci + 1 < line.len() && li > 0 && li + 1 < input.len() {
-                let chars = [
-                    input[li - 1][ci - 1],
-                    input[li - 1][ci + 1],
-                    input[li + 1][ci + 1],
-                    input[li + 1][ci - 1],
-                ];
-
-                if chars == ['M', 'M', 'S', 'S']
-                    || chars == ['M', 'S', 'S', 'M']
-                    || chars == ['S', 'S', 'M', 'M']
-                    || chars == ['S', 'M', 'M', 'S']
-                {
-                    count += 1;
-                }
-            }
-        }
-    }
+/// Finds every word of the dictionary in `dictionary_path` in the grid in `path`.
+///
+/// Returns the words with their start cell and direction.
+///
+/// # Errors
+///
+/// This function will return an error if a file cannot be read or if the grid is invalid.
+pub fn solve_dictionary_day04(
+    path: &str,
+    dictionary_path: &str,
+) -> Result<Vec<(String, search::WordMatch)>, Box<dyn Error>> {
+    let content = fs::read_to_string(path)?;
+    let data = parse_chars(&content, Shape::Ragged)?;
+    let dictionary = Dictionary::parse(&fs::read_to_string(dictionary_path)?);
 
-    count
+    Ok(dictionary
+        .solve(&data)
+        .into_iter()
+        .map(|found| (found.word.to_string(), found.location))
+        .collect())
 }
 
-#[allow(dead_code, clippy::too_many_lines)]
-fn count_word(input: &[Vec<char>]) -> i32 {
-    const WORD: [char; 4] = ['X', 'M', 'A', 'S'];
-    let mut count = 0;
+/// Generates a word search grid as described by `spec`.
+///
+/// # Errors
+///
+/// This function will return an error if no grid satisfying `spec` could be generated.
+pub fn generate_day04(spec: &generator::PuzzleSpec) -> Result<String, Box<dyn Error>> {
+    Ok(generator::generate(spec)?.to_string())
+}
 
-    for (li, line) in input.iter().enumerate() {
-        for (ci, c) in line.iter().enumerate() {
-            if c != &WORD[0] {
-                continue;
-            }
+fn cross_stencil() -> Stencil {
+    Stencil::parse("M.S\n.A.\nM.S", '.')
+}
 
-            if ci >= 3 {
-                if line[ci - 1] == WORD[1] && line[ci - 2] == WORD[2] && line[ci - 3] == WORD[3] {
-                    count += 1;
-                }
-                if li >= 3
-                    && input[li - 1][ci - 1] == WORD[1]
-                    && input[li - 2][ci - 2] == WORD[2]
-                    && input[li - 3][ci - 3] == WORD[3]
-                {
-                    count += 1;
-                }
-                if li + 3 < input.len()
-                    && input[li + 1][ci - 1] == WORD[1]
-                    && input[li + 2][ci - 2] == WORD[2]
-                    && input[li + 3][ci - 3] == WORD[3]
-                {
-                    count += 1;
-                }
-            }
-            if ci + 3 < line.len() {
-                if line[ci + 1] == WORD[1] && line[ci + 2] == WORD[2] && line[ci + 3] == WORD[3] {
-                    count += 1;
-                }
-                if li >= 3
-                    && input[li - 1][ci + 1] == WORD[1]
-                    && input[li - 2][ci + 2] == WORD[2]
-                    && input[li - 3][ci + 3] == WORD[3]
-                {
-                    count += 1;
-                }
-                if li + 3 < input.len()
-                    && input[li + 1][ci + 1] == WORD[1]
-                    && input[li + 2][ci + 2] == WORD[2]
-                    && input[li + 3][ci + 3] == WORD[3]
-                {
-                    count += 1;
-                }
-            }
-            if li >= 3
-                && input[li - 1][ci] == WORD[1]
-                && input[li - 2][ci] == WORD[2]
-                && input[li - 3][ci] == WORD[3]
-            {
-                count += 1;
-            }
-            if li + 3 < input.len()
-                && input[li + 1][ci] == WORD[1]
-                && input[li + 2][ci] == WORD[2]
-                && input[li + 3][ci] == WORD[3]
-            {
-                count += 1;
-            }
-        }
-    }
+/// Counts the X-MAS crosses in `input`.
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+pub fn count_cross(input: &Grid<char>) -> i32 {
+    find_stencil(input, &cross_stencil()).len() as i32
+}
 
-    count
+/// Counts the occurrences of `XMAS` in all eight directions in `input`.
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+pub fn count_word(input: &Grid<char>) -> i32 {
+    find_word(input, "XMAS", &Direction::ALL).len() as i32
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+    use search::WordMatch;
+
+    fn string_to_arrays(input: &str) -> Grid<char> {
+        parse_chars(input, Shape::Ragged).expect("ragged grids always parse")
+    }
 
     #[test]
     fn test_day04_part_one() {
@@ -144,8 +99,8 @@ MAMMMXMMMM
 MXMXAXMASX";
 
         let data = string_to_arrays(input);
-        assert_eq!(10, data.len());
-        assert_eq!(10, data[0].len());
+        assert_eq!(10, data.height());
+        assert_eq!(10, data.width());
 
         let want = 18;
         let got = count_word(&data);
@@ -167,11 +122,292 @@ MAMMMXMMMM
 MXMXAXMASX";
 
         let data = string_to_arrays(input);
-        assert_eq!(10, data.len());
-        assert_eq!(10, data[0].len());
+        assert_eq!(10, data.height());
+        assert_eq!(10, data.width());
 
         let want = 9;
         let got = count_cross(&data);
         assert_eq!(want, got);
     }
+
+    #[test]
+    fn test_day04_find_word() {
+        let data = string_to_arrays(
+            "XMAS
+MMXA
+AXAM
+SSSX",
+        );
+
+        let got = find_word(&data, "XMAS", &Direction::ALL);
+        assert_eq!(
+            got,
+            vec![
+                WordMatch {
+                    row: 0,
+                    column: 0,
+                    direction: Direction::East,
+                },
+                WordMatch {
+                    row: 0,
+                    column: 0,
+                    direction: Direction::South,
+                },
+                WordMatch {
+                    row: 3,
+                    column: 3,
+                    direction: Direction::North,
+                },
+            ]
+        );
+
+        let got = find_word(&data, "XMAS", &Direction::DIAGONAL);
+        assert!(got.is_empty());
+
+        let got = find_word(&data, "AS", &Direction::ORTHOGONAL);
+        assert_eq!(got.len(), 4);
+        assert!(find_word(&data, "", &Direction::ALL).is_empty());
+    }
+
+    #[test]
+    fn test_day04_stencil() {
+        let cross = Stencil::parse("M.S\n.A.\nM.S", '.');
+        assert_eq!(cross.variants().len(), 4);
+
+        let corner = Stencil::parse("AB\nC", '.');
+        assert_eq!((corner.height(), corner.width()), (2, 2));
+        assert_eq!(corner.rotate(), Stencil::parse("CA\n.B", '.'));
+        assert_eq!(corner.reflect(), Stencil::parse("BA\n.C", '.'));
+        assert_eq!(corner.variants().len(), 8);
+
+        let data = string_to_arrays(
+            "XAB
+XCX
+BAX",
+        );
+        let got: Vec<(usize, usize)> = find_stencil(&data, &corner)
+            .iter()
+            .map(|m| (m.row, m.column))
+            .collect();
+        assert_eq!(got, vec![(0, 1), (1, 0)]);
+    }
+
+    #[test]
+    fn test_day04_grid_shape() {
+        let data = parse_chars("XMAS\r\nMXMA\r\nSAXS\r\n\r\n", Shape::Rectangular).unwrap();
+        assert_eq!(data, string_to_arrays("XMAS\nMXMA\nSAXS\n"));
+        assert_eq!(data.height(), 3);
+        assert_eq!(data.width(), 4);
+
+        let ragged = "XMASX\nM\nAS\nS";
+        assert_eq!(
+            parse_chars(ragged, Shape::Rectangular),
+            Err(grid::ShapeError {
+                line: 2,
+                expected: 5,
+                found: 1,
+            })
+        );
+
+        let data = parse_chars(ragged, Shape::Ragged).unwrap();
+        assert_eq!(count_word(&data), 2);
+        assert_eq!(count_cross(&data), 0);
+    }
+
+    #[test]
+    fn test_day04_graphemes() {
+        let input = "e\u{301}ßa\u{308}\nßxx\na\u{308}xx";
+        let data = grid::parse_graphemes(input, Shape::Rectangular).unwrap();
+        assert_eq!(data.width(), 3);
+
+        let word = grid::graphemes("e\u{301}ßa\u{308}");
+        let got = search::find_sequence(&data, &word, &Direction::ALL);
+        assert_eq!(got.len(), 2);
+
+        let word = grid::graphemes("ßa\u{308}");
+        let got = search::find_sequence(&data, &word, &Direction::ALL);
+        assert_eq!(got.len(), 2);
+    }
+
+    #[test]
+    fn test_day04_dictionary() {
+        let input = "MMMSXXMASM
+MSAMXMSMSA
+AMXSXMAAMM
+MSAMASMSMX
+XMASAMXAMM
+XXAMMXXAMA
+SMSMSASXSS
+SAXAMASAAA
+MAMMMXMMMM
+MXMXAXMASX";
+        let data = string_to_arrays(input);
+
+        let dictionary = Dictionary::parse("XMAS\nSAMX\n\nMAS\nXMAS\nQ\nX\n");
+        assert_eq!(dictionary.len(), 5);
+
+        let found = dictionary.solve(&data);
+        for word in ["XMAS", "SAMX", "MAS"] {
+            let want = find_word(&data, word, &Direction::ALL);
+            let got: Vec<_> = found
+                .iter()
+                .filter(|f| f.word == word)
+                .map(|f| f.location)
+                .collect();
+            assert_eq!(want, got, "{word}");
+        }
+
+        let xs = data.find_all(&'X').len();
+        assert_eq!(found.iter().filter(|f| f.word == "X").count(), xs);
+        assert!(found.iter().all(|f| f.word != "Q"));
+        assert!(
+            Dictionary::new(Vec::<String>::new())
+                .solve(&data)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_day04_generator() {
+        for seed in 0..5 {
+            let spec = generator::PuzzleSpec {
+                width: 10,
+                height: 10,
+                word: "XMAS".to_string(),
+                count: 7,
+                crosses: Some(3),
+                seed,
+            };
+            let grid = generator::generate(&spec).unwrap();
+            assert_eq!(grid.height(), 10);
+            assert_eq!(grid.width(), 10);
+            assert_eq!(count_word(&grid), 7);
+            assert_eq!(count_cross(&grid), 3);
+
+            assert_eq!(grid, generator::generate(&spec).unwrap());
+        }
+
+        let spec = generator::PuzzleSpec {
+            width: 8,
+            height: 6,
+            word: "CAT".to_string(),
+            count: 4,
+            crosses: None,
+            seed: 42,
+        };
+        let grid = generator::generate(&spec).unwrap();
+        assert_eq!(find_word(&grid, "CAT", &Direction::ALL).len(), 4);
+
+        let spec = generator::PuzzleSpec {
+            width: 3,
+            height: 3,
+            word: "XMAS".to_string(),
+            count: 1,
+            crosses: None,
+            seed: 0,
+        };
+        assert_eq!(
+            generator::generate(&spec),
+            Err(generator::GenerateError::TooManyWords {
+                count: 1,
+                positions: 0,
+            })
+        );
+
+        let spec = generator::PuzzleSpec {
+            width: 4,
+            height: 4,
+            word: "X".to_string(),
+            count: 8,
+            crosses: None,
+            seed: 0,
+        };
+        let grid = generator::generate(&spec).unwrap();
+        assert_eq!(grid.find_all(&'X').len(), 1);
+        assert_eq!(count_word(&grid), 0);
+        assert_eq!(
+            generator::generate(&generator::PuzzleSpec { count: 1, ..spec }),
+            Err(generator::GenerateError::UnreachableCount {
+                count: 1,
+                multiple: 8,
+            })
+        );
+    }
+
+    #[test]
+    fn test_day04_byte_grid() {
+        let input = "MMMSXXMASM
+MSAMXMSMSA
+AMXSXMAAMM
+MSAMASMSMX
+XMASAMXAMM
+XXAMMXXAMA
+SMSMSASXSS
+SAXAMASAAA
+MAMMMXMMMM
+MXMXAXMASX";
+        let grid = bytes::ByteGrid::parse(input).unwrap();
+        assert_eq!((grid.width(), grid.height()), (10, 10));
+        assert_eq!(grid.get(4, 0), Some(b'X'));
+        assert_eq!(grid.get(10, 0), None);
+        assert_eq!(grid.line_scans().count_word(b"XMAS"), 18);
+        assert_eq!(grid.count_cross(), 9);
+
+        assert!(bytes::ByteGrid::parse("XMAS\nXM").is_err());
+        assert_eq!(
+            bytes::ByteGrid::parse("\nXMAS\r\n\r\n"),
+            bytes::ByteGrid::parse("XMAS")
+        );
+        assert_eq!(
+            parse_chars("\nXMAS\n", Shape::Rectangular),
+            parse_chars("XMAS", Shape::Rectangular)
+        );
+        assert_eq!(
+            bytes::ByteGrid::parse("XMAS\nXMäS"),
+            Err(bytes::ByteGridError::NotAscii {
+                line: 2,
+                column: 3,
+                found: 'ä',
+            })
+        );
+        let empty = bytes::ByteGrid::parse("").unwrap();
+        assert_eq!(empty.line_scans().count_word(b"XMAS"), 0);
+        assert_eq!(empty.count_cross(), 0);
+    }
+
+    fn grid_strategy() -> impl Strategy<Value = String> {
+        (1usize..9, 1usize..9).prop_flat_map(|(width, height)| {
+            proptest::collection::vec(
+                proptest::collection::vec(
+                    prop_oneof![Just('X'), Just('M'), Just('A'), Just('S')],
+                    width,
+                ),
+                height,
+            )
+            .prop_map(|rows| {
+                rows.iter()
+                    .map(|row| row.iter().collect::<String>())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_day04_byte_grid_matches_char_grid(input in grid_strategy()) {
+            let data = string_to_arrays(&input);
+            let grid = bytes::ByteGrid::parse(&input).unwrap();
+            let scans = grid.line_scans();
+
+            prop_assert_eq!(scans.count_word(b"XMAS"), count_word(&data) as usize);
+            prop_assert_eq!(grid.count_cross(), count_cross(&data) as usize);
+            for word in ["MAS", "AA", "X", "SAMAS"] {
+                prop_assert_eq!(
+                    scans.count_word(word.as_bytes()),
+                    find_word(&data, word, &Direction::ALL).len()
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,67 @@
+use crate::geometry::Point;
+use crate::grid::Grid;
+
+/// One of the eight directions a word can be read in.
+pub use crate::geometry::Direction8 as Direction;
+
+/// A word found in a grid, starting at `(row, column)` and read towards `direction`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WordMatch {
+    pub row: usize,
+    pub column: usize,
+    pub direction: Direction,
+}
+
+impl WordMatch {
+    /// The cell of the `index`-th character of the match.
+    #[allow(clippy::cast_possible_wrap)]
+    pub const fn cell(&self, index: usize) -> Point {
+        let delta = self.direction.delta();
+        Point::new(
+            self.column as isize + delta.x * index as isize,
+            self.row as isize + delta.y * index as isize,
+        )
+    }
+}
+
+/// Finds every occurrence of `word` in `grid` read in any of `directions`.
+///
+/// Matches are returned in row-major order of their start cell and, per cell,
+/// in the order of `directions`.
+pub fn find_word(grid: &Grid<char>, word: &str, directions: &[Direction]) -> Vec<WordMatch> {
+    let word: Vec<char> = word.chars().collect();
+    find_sequence(grid, &word, directions)
+}
+
+/// Like [`find_word`], but for grids of arbitrary cells, e.g. grapheme clusters.
+#[allow(clippy::cast_sign_loss)]
+pub fn find_sequence<T: PartialEq>(
+    grid: &Grid<T>,
+    word: &[T],
+    directions: &[Direction],
+) -> Vec<WordMatch> {
+    let Some(first) = word.first() else {
+        return Vec::new();
+    };
+
+    let mut matches = Vec::new();
+    for start in grid.find_all(first) {
+        for &direction in directions {
+            let candidate = WordMatch {
+                row: start.y as usize,
+                column: start.x as usize,
+                direction,
+            };
+            if word
+                .iter()
+                .enumerate()
+                .skip(1)
+                .all(|(index, expected)| grid.get(candidate.cell(index)) == Some(expected))
+            {
+                matches.push(candidate);
+            }
+        }
+    }
+
+    matches
+}
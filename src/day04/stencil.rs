@@ -0,0 +1,123 @@
+use crate::grid::Grid;
+
+/// A small 2D template of characters, where `None` cells match anything.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Stencil {
+    cells: Grid<Option<char>>,
+}
+
+/// A stencil variant found in a grid with its top left corner at `(row, column)`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StencilMatch {
+    pub row: usize,
+    pub column: usize,
+    pub variant: Stencil,
+}
+
+impl Stencil {
+    /// Parses a template with one row per line. Every `wildcard` character, as
+    /// well as every cell missing from a row shorter than the widest one,
+    /// matches any character.
+    pub fn parse(template: &str, wildcard: char) -> Self {
+        let rows = template
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .map(|c| Some(c).filter(|c| *c != wildcard))
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            cells: Grid::from_ragged_rows(rows),
+        }
+    }
+
+    pub const fn height(&self) -> usize {
+        self.cells.height()
+    }
+
+    pub const fn width(&self) -> usize {
+        self.cells.width()
+    }
+
+    /// The stencil rotated by 90 degrees clockwise.
+    ///
+    /// # Panics
+    ///
+    /// Never panics: the columns of a rectangular grid all have the same length.
+    #[must_use]
+    pub fn rotate(&self) -> Self {
+        let rows =
+            (0..self.width()).map(|column| self.cells.column(column).rev().copied().collect());
+
+        Self {
+            cells: Grid::from_rows(rows).expect("columns have equal length"),
+        }
+    }
+
+    /// The stencil mirrored along its vertical axis.
+    ///
+    /// # Panics
+    ///
+    /// Never panics: the rows of a rectangular grid all have the same length.
+    #[must_use]
+    pub fn reflect(&self) -> Self {
+        let rows = self
+            .cells
+            .rows()
+            .map(|row| row.iter().rev().copied().collect());
+
+        Self {
+            cells: Grid::from_rows(rows).expect("rows have equal length"),
+        }
+    }
+
+    /// All distinct rotations and reflections of the stencil, starting with itself.
+    pub fn variants(&self) -> Vec<Self> {
+        let mut variants: Vec<Self> = Vec::with_capacity(8);
+        let mut current = self.clone();
+        for _ in 0..4 {
+            for candidate in [current.clone(), current.reflect()] {
+                if !variants.contains(&candidate) {
+                    variants.push(candidate);
+                }
+            }
+            current = current.rotate();
+        }
+        variants
+    }
+
+    /// Whether the stencil matches `grid` with its top left corner at `(row, column)`.
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn matches_at(&self, grid: &Grid<char>, row: usize, column: usize) -> bool {
+        self.cells.iter().all(|(point, expected)| match expected {
+            Some(expected) => {
+                grid.get(point.offset(column as isize, row as isize)) == Some(expected)
+            }
+            None => true,
+        })
+    }
+}
+
+/// Finds every placement of any rotation or reflection of `stencil` in `grid`.
+pub fn find_stencil(grid: &Grid<char>, stencil: &Stencil) -> Vec<StencilMatch> {
+    let variants = stencil.variants();
+    let mut matches = Vec::new();
+
+    for row in 0..grid.height() {
+        for column in 0..grid.width() {
+            for variant in &variants {
+                if variant.matches_at(grid, row, column) {
+                    matches.push(StencilMatch {
+                        row,
+                        column,
+                        variant: variant.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    matches
+}
@@ -0,0 +1,113 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::Write;
+
+use super::validate_update;
+
+/// Controls which part of the rule graph is exported and how.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DotOptions<'a> {
+    /// Only export the pages of this update and the rules between them.
+    pub restrict_to: Option<&'a [i32]>,
+    /// Highlight the rules violated by this update.
+    pub highlight: Option<&'a [i32]>,
+    /// Drop rules implied by a longer chain of rules. Violated rules are
+    /// always kept. On cyclic rule sets the kept rules are one minimal choice
+    /// that preserves which pages can reach which.
+    pub transitive_reduction: bool,
+}
+
+/// Renders the page ordering rules as a Graphviz DOT graph.
+///
+/// Pages and rules are emitted in ascending order so the output is stable.
+pub fn to_dot(rules: &HashMap<i32, Vec<i32>>, options: &DotOptions<'_>) -> String {
+    let allowed: Option<HashSet<i32>> = options
+        .restrict_to
+        .map(|update| update.iter().copied().collect());
+    let included = |page: &i32| allowed.as_ref().is_none_or(|pages| pages.contains(page));
+
+    let mut edges: BTreeSet<(i32, i32)> = rules
+        .iter()
+        .flat_map(|(before, afters)| afters.iter().map(move |after| (*before, *after)))
+        .filter(|(before, after)| included(before) && included(after))
+        .collect();
+    let mut pages: BTreeSet<i32> = edges.iter().flat_map(|(a, b)| [*a, *b]).collect();
+    if let Some(update) = options.restrict_to {
+        pages.extend(update);
+    }
+
+    let violated: HashSet<(i32, i32)> = options
+        .highlight
+        .map(|update| {
+            validate_update(update, rules)
+                .into_iter()
+                .map(|violation| (violation.before, violation.after))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if options.transitive_reduction {
+        reduce(&mut edges, &violated);
+    }
+
+    let mut dot = String::from("digraph rules {\n");
+    for page in &pages {
+        let _ = writeln!(dot, "    {page};");
+    }
+    for (before, after) in &edges {
+        if violated.contains(&(*before, *after)) {
+            let _ = writeln!(dot, "    {before} -> {after} [color=red, penwidth=2];");
+        } else {
+            let _ = writeln!(dot, "    {before} -> {after};");
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Removes the edges not in `keep` one at a time, as long as their target
+/// stays reachable through the remaining ones.
+///
+/// Checking every edge against the current rather than the original edge set
+/// matters for cycles: there, every edge has an alternative path in the
+/// original graph, but removing all of them would disconnect the cycle.
+fn reduce(edges: &mut BTreeSet<(i32, i32)>, keep: &HashSet<(i32, i32)>) {
+    let mut successors: HashMap<i32, BTreeSet<i32>> = HashMap::new();
+    for (a, b) in edges.iter() {
+        successors.entry(*a).or_default().insert(*b);
+    }
+
+    edges.retain(|&(from, to)| {
+        if keep.contains(&(from, to)) {
+            return true;
+        }
+        successors
+            .get_mut(&from)
+            .expect("edge has a source")
+            .remove(&to);
+        if reachable(&successors, from, to) {
+            false
+        } else {
+            successors
+                .get_mut(&from)
+                .expect("edge has a source")
+                .insert(to);
+            true
+        }
+    });
+}
+
+fn reachable(successors: &HashMap<i32, BTreeSet<i32>>, from: i32, to: i32) -> bool {
+    let mut seen = HashSet::from([from]);
+    let mut stack = vec![from];
+    while let Some(page) = stack.pop() {
+        for next in successors.get(&page).into_iter().flatten() {
+            if *next == to {
+                return true;
+            }
+            if seen.insert(*next) {
+                stack.push(*next);
+            }
+        }
+    }
+    false
+}
@@ -0,0 +1,109 @@
+use std::{error::Error, fmt};
+
+/// What to do with an update that has no single middle page, i.e. an empty
+/// one or one with an even number of pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MiddlePolicy {
+    /// Fail with a [`MiddleError`].
+    #[default]
+    Error,
+    /// Leave the update out of the sum and report it as skipped.
+    Skip,
+    /// Use the lower of the two middle pages.
+    Lower,
+    /// Use the upper of the two middle pages.
+    Upper,
+    /// Use the average of the two middle pages, rounded towards zero.
+    Average,
+}
+
+impl MiddlePolicy {
+    /// The middle page of `update`, or `None` if the update is skipped.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `update` has no single middle page and the
+    /// policy is [`Self::Error`], or if `update` is empty and the policy picks a page.
+    pub fn middle(self, update: &[i32]) -> Result<Option<i32>, MiddleError> {
+        let half = update.len() / 2;
+        if update.len() % 2 == 1 {
+            return Ok(Some(update[half]));
+        }
+
+        let error = || MiddleError {
+            update: update.to_vec(),
+        };
+        match self {
+            Self::Error => Err(error()),
+            Self::Skip => Ok(None),
+            _ if update.is_empty() => Err(error()),
+            Self::Lower => Ok(Some(update[half - 1])),
+            Self::Upper => Ok(Some(update[half])),
+            Self::Average => Ok(Some(update[half - 1].midpoint(update[half]))),
+        }
+    }
+}
+
+impl fmt::Display for MiddlePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Error => "error",
+            Self::Skip => "skip",
+            Self::Lower => "lower",
+            Self::Upper => "upper",
+            Self::Average => "average",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl std::str::FromStr for MiddlePolicy {
+    type Err = std::io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "error" => Ok(Self::Error),
+            "skip" => Ok(Self::Skip),
+            "lower" => Ok(Self::Lower),
+            "upper" => Ok(Self::Upper),
+            "average" => Ok(Self::Average),
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "Invalid middle page policy '{s}'. Expected one of: error, skip, lower, upper, average"
+                ),
+            )),
+        }
+    }
+}
+
+/// An update without a single middle page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MiddleError {
+    pub update: Vec<i32>,
+}
+
+impl fmt::Display for MiddleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.update.is_empty() {
+            return write!(f, "Update is empty and has no middle page");
+        }
+        let pages: Vec<String> = self.update.iter().map(ToString::to_string).collect();
+        write!(
+            f,
+            "Update {} has {} pages and no single middle page",
+            pages.join(","),
+            self.update.len()
+        )
+    }
+}
+
+impl Error for MiddleError {}
+
+/// The sum of the middle pages of several updates.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MiddleSum {
+    pub sum: i32,
+    /// The updates left out by [`MiddlePolicy::Skip`].
+    pub skipped: Vec<Vec<i32>>,
+}
@@ -1,20 +1,45 @@
-use std::{collections::HashMap, error::Error, fs};
+use std::{collections::HashMap, error::Error, fmt, fs};
+
+use dot::{DotOptions, to_dot};
+use middle::{MiddleError, MiddlePolicy, MiddleSum};
+use precedence::Precedence;
+
+use crate::parse::{self, comma_list, parse_lines, pipe_pair, unsigned};
+
+pub mod dot;
+pub mod middle;
+pub mod precedence;
+pub mod repair;
 
 /// Solves the problem for day 05.
 ///
 /// # Errors
 ///
 /// This function will return an error if the file cannot be read or if the input is invalid.
-#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
 pub fn solve_day05(path: &str) -> Result<(i32, i32), Box<dyn Error>> {
+    let (part_one, part_two) = solve_day05_with_policy(path, MiddlePolicy::default())?;
+    Ok((part_one.sum, part_two.sum))
+}
+
+/// Solves the problem for day 05, treating updates without a single middle
+/// page according to `policy`.
+///
+/// # Errors
+///
+/// This function will return an error if the file cannot be read or if the input is invalid.
+pub fn solve_day05_with_policy(
+    path: &str,
+    policy: MiddlePolicy,
+) -> Result<(MiddleSum, MiddleSum), Box<dyn Error>> {
     let content = fs::read_to_string(path)?;
 
-    let (rules, updates) = parse_input(&content);
+    let (rules, updates) = parse_input(&content)?;
+    let precedence = Precedence::new(&rules);
 
     let ordered_updates = updates
         .iter()
-        .map(|update| order_updates(update, &rules))
-        .collect::<Vec<_>>();
+        .map(|update| order_updates(update, &precedence))
+        .collect::<Result<Vec<_>, _>>()?;
 
     let result_one = updates
         .iter()
@@ -29,69 +54,344 @@ pub fn solve_day05(path: &str) -> Result<(i32, i32), Box<dyn Error>> {
         .filter(|(a, b)| a != b)
         .map(|(_, b)| b.clone())
         .collect::<Vec<_>>();
-    let part_one = sum_middle_value(&result_one);
-    let part_two = sum_middle_value(&result_two);
+    let part_one = sum_middle_value(&result_one, policy)?;
+    let part_two = sum_middle_value(&result_two, policy)?;
     Ok((part_one, part_two))
 }
 
-fn parse_input(input: &str) -> (HashMap<i32, Vec<i32>>, Vec<Vec<i32>>) {
-    let parts: Vec<_> = input.split("\n\n").collect();
-    let mut map: HashMap<i32, Vec<i32>> = HashMap::new();
-    parts[0].split('\n').for_each(|r| {
-        let values = r
-            .split('|')
-            .map(|i| i.parse().unwrap())
-            .collect::<Vec<i32>>();
-
-        let index = values[0];
-        map.entry(index)
-            .or_default()
-            .append(values[1..].to_vec().as_mut());
-    });
-    // .collect();
-
-    let updates: Vec<Vec<i32>> = parts[1]
-        .split('\n')
-        .map(|r| r.split(',').map(|i| i.parse().unwrap()).collect())
-        .collect();
+/// The page ordering rules, keyed by the page that has to come first, and the updates.
+type Input = (HashMap<i32, Vec<i32>>, Vec<Vec<i32>>);
 
-    (map, updates)
+/// A line of the input that does not follow the `a|b` or `a,b,c` grammar,
+/// with the line and column where parsing failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputError {
+    InvalidRule(parse::ParseError),
+    InvalidUpdate(parse::ParseError),
 }
 
-fn order_updates(updates: &[i32], rules: &HashMap<i32, Vec<i32>>) -> Vec<i32> {
-    if !updates.iter().any(|u| rules.contains_key(u)) {
-        return updates.to_vec();
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (kind, hint, error) = match self {
+            Self::InvalidRule(error) => ("rule", "Rules look like '47|53'", error),
+            Self::InvalidUpdate(error) => ("update", "Updates look like '75,47,61'", error),
+        };
+        write!(
+            f,
+            "Invalid {kind} on line {}, column {}: expected {} in '{}'. {hint}",
+            error.line, error.column, error.expected, error.text
+        )
     }
+}
+
+impl Error for InputError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::InvalidRule(error) | Self::InvalidUpdate(error) => Some(error),
+        }
+    }
+}
+
+/// Parses the rules and updates.
+///
+/// Rules come first, one `a|b` per line, followed by a blank line and one
+/// comma separated update per line. `\r\n` line endings, surrounding
+/// whitespace and blank lines before the rules or after the updates are
+/// accepted.
+fn parse_input(input: &str) -> Result<Input, InputError> {
+    let mut sections = parse::sections(input).into_iter();
+    let rules = sections.next().unwrap_or_default();
+    let updates: Vec<parse::Line> = sections.flatten().collect();
+
+    let mut map: HashMap<i32, Vec<i32>> = HashMap::new();
+    let rules = parse_lines(&rules, pipe_pair(unsigned)).map_err(InputError::InvalidRule)?;
+    for (before, after) in rules {
+        map.entry(before).or_default().push(after);
+    }
+
+    let updates = parse_lines(&updates, comma_list(unsigned)).map_err(InputError::InvalidUpdate)?;
 
-    let mut ordered_updates: Vec<i32> = updates.to_vec();
+    Ok((map, updates))
+}
 
-    ordered_updates.sort_by(|a, b| {
-        for (key, values) in rules {
-            if a == key && values.contains(b) {
-                return std::cmp::Ordering::Less;
+/// A rule `before|after` that an update breaks by placing `after` first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub before: i32,
+    pub after: i32,
+    pub before_position: usize,
+    pub after_position: usize,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "rule {}|{} violated: {} at position {} comes after {} at position {}",
+            self.before,
+            self.after,
+            self.before,
+            self.before_position,
+            self.after,
+            self.after_position
+        )
+    }
+}
+
+/// Returns every rule broken by `update`, ordered by the positions involved.
+pub fn validate_update(update: &[i32], rules: &HashMap<i32, Vec<i32>>) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    for (after_position, after) in update.iter().enumerate() {
+        for (offset, before) in update[after_position + 1..].iter().enumerate() {
+            if rules.get(before).is_some_and(|pages| pages.contains(after)) {
+                violations.push(Violation {
+                    before: *before,
+                    after: *after,
+                    before_position: after_position + 1 + offset,
+                    after_position,
+                });
             }
         }
-        std::cmp::Ordering::Equal
-    });
-    ordered_updates
+    }
+    violations
 }
 
-fn sum_middle_value(input: &[Vec<i32>]) -> i32 {
-    input
-        .iter()
-        .filter_map(|row| {
-            if row.is_empty() || row.len() % 2 == 0 {
-                None
-            } else {
-                Some(row[row.len() / 2])
-            }
+/// An incorrectly ordered update and the rules it violates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    pub update: Vec<i32>,
+    pub violations: Vec<Violation>,
+}
+
+/// Lists every incorrectly ordered update of the input in `path` together
+/// with the rules it violates.
+///
+/// # Errors
+///
+/// This function will return an error if the file cannot be read or if the input is invalid.
+pub fn explain_day05(path: &str) -> Result<Vec<Explanation>, Box<dyn Error>> {
+    let content = fs::read_to_string(path)?;
+
+    let (rules, updates) = parse_input(&content)?;
+
+    Ok(updates
+        .into_iter()
+        .map(|update| Explanation {
+            violations: validate_update(&update, &rules),
+            update,
         })
-        .sum()
+        .filter(|explanation| !explanation.violations.is_empty())
+        .collect())
+}
+
+/// How far an incorrectly ordered update is from a correct order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repair {
+    pub update: Vec<i32>,
+    /// The minimum number of pages that have to be moved.
+    pub moves: usize,
+    /// The number of correct orderings of the pages, `None` if there are too
+    /// many to count.
+    pub orderings: Option<u128>,
+}
+
+/// Lists every incorrectly ordered update of the input in `path` with the
+/// minimum number of moves to repair it and the number of correct orderings.
+///
+/// # Errors
+///
+/// This function will return an error if the file cannot be read, if the input is invalid
+/// or if the rules between the pages of an update contain a cycle.
+pub fn repair_day05(path: &str) -> Result<Vec<Repair>, Box<dyn Error>> {
+    let content = fs::read_to_string(path)?;
+
+    let (rules, updates) = parse_input(&content)?;
+    let precedence = Precedence::new(&rules);
+
+    let mut repairs = Vec::new();
+    for update in updates {
+        if order_updates(&update, &precedence)? != update {
+            repairs.push(Repair {
+                moves: repair::min_moves(&update, &precedence),
+                orderings: repair::count_orderings(&update, &precedence),
+                update,
+            });
+        }
+    }
+    Ok(repairs)
+}
+
+/// Renders the rules of the input in `path` as a Graphviz DOT graph.
+///
+/// If `update` is given, the graph is restricted to the pages of that update
+/// (counted from 1) and the rules it violates are highlighted.
+///
+/// # Errors
+///
+/// This function will return an error if the file cannot be read, if the input is invalid
+/// or if there is no such update.
+pub fn export_dot_day05(
+    path: &str,
+    update: Option<usize>,
+    transitive_reduction: bool,
+) -> Result<String, Box<dyn Error>> {
+    let content = fs::read_to_string(path)?;
+
+    let (rules, updates) = parse_input(&content)?;
+    let update = match update {
+        Some(index) => Some(
+            index
+                .checked_sub(1)
+                .and_then(|index| updates.get(index))
+                .ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        format!(
+                            "Invalid update {index}. There are {} updates",
+                            updates.len()
+                        ),
+                    )
+                })?
+                .as_slice(),
+        ),
+        None => None,
+    };
+
+    Ok(to_dot(
+        &rules,
+        &DotOptions {
+            restrict_to: update,
+            highlight: update,
+            transitive_reduction,
+        },
+    ))
+}
+
+/// The rules restricted to the pages of an update contain a cycle, so the
+/// update cannot be ordered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleError {
+    /// The pages forming the cycle, each one required before the next and the
+    /// last one required before the first.
+    pub pages: Vec<i32>,
+}
+
+impl fmt::Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pages: Vec<String> = self
+            .pages
+            .iter()
+            .chain(self.pages.first())
+            .map(ToString::to_string)
+            .collect();
+        write!(f, "Rules contain a cycle: {}", pages.join(" -> "))
+    }
+}
+
+impl Error for CycleError {}
+
+/// Orders `updates` by a topological sort of the rules between its pages.
+///
+/// Among the pages whose predecessors are all placed, the one appearing first
+/// in `updates` is placed next, so correctly ordered updates stay unchanged.
+///
+/// # Errors
+///
+/// This function will return an error if the rules between the pages contain a cycle.
+pub fn order_updates(updates: &[i32], precedence: &Precedence) -> Result<Vec<i32>, CycleError> {
+    let pages: Vec<Option<usize>> = updates.iter().map(|&page| precedence.index(page)).collect();
+    topological_order(updates, |a, b| match (pages[a], pages[b]) {
+        (Some(a), Some(b)) => precedence.before_index(a, b),
+        _ => false,
+    })
+}
+
+/// Same as [`order_updates`], but scans the rules for every comparison.
+///
+/// # Errors
+///
+/// This function will return an error if the rules between the pages contain a cycle.
+pub fn order_updates_naive(
+    updates: &[i32],
+    rules: &HashMap<i32, Vec<i32>>,
+) -> Result<Vec<i32>, CycleError> {
+    topological_order(updates, |a, b| {
+        rules
+            .get(&updates[a])
+            .is_some_and(|after| after.contains(&updates[b]))
+    })
+}
+
+/// Sorts `updates`, where `precedes` compares the pages at two positions.
+fn topological_order(
+    updates: &[i32],
+    precedes: impl Fn(usize, usize) -> bool,
+) -> Result<Vec<i32>, CycleError> {
+    let before = |a: usize, b: usize| a != b && precedes(a, b);
+
+    let mut in_degree: Vec<usize> = (0..updates.len())
+        .map(|b| (0..updates.len()).filter(|&a| before(a, b)).count())
+        .collect();
+    let mut remaining: Vec<usize> = (0..updates.len()).collect();
+    let mut ordered_updates = Vec::with_capacity(updates.len());
+
+    while !remaining.is_empty() {
+        let Some(position) = remaining.iter().position(|&page| in_degree[page] == 0) else {
+            return Err(find_cycle(updates, &remaining, before));
+        };
+
+        let page = remaining.remove(position);
+        ordered_updates.push(updates[page]);
+        for &next in &remaining {
+            if before(page, next) {
+                in_degree[next] -= 1;
+            }
+        }
+    }
+
+    Ok(ordered_updates)
+}
+
+/// Finds a cycle among `remaining`, where every page has a predecessor.
+fn find_cycle(
+    updates: &[i32],
+    remaining: &[usize],
+    before: impl Fn(usize, usize) -> bool,
+) -> CycleError {
+    let mut path = vec![remaining[0]];
+    loop {
+        let current = *path.last().expect("path is never empty");
+        let previous = *remaining
+            .iter()
+            .find(|&&candidate| before(candidate, current))
+            .expect("every remaining page has a predecessor");
+
+        if let Some(start) = path.iter().position(|&page| page == previous) {
+            let mut cycle: Vec<i32> = path[start..].iter().map(|&page| updates[page]).collect();
+            cycle.reverse();
+            return CycleError { pages: cycle };
+        }
+        path.push(previous);
+    }
+}
+
+/// Sums the middle pages of `input`, handling updates without a single middle
+/// page according to `policy`.
+fn sum_middle_value(input: &[Vec<i32>], policy: MiddlePolicy) -> Result<MiddleSum, MiddleError> {
+    let mut result = MiddleSum::default();
+    for update in input {
+        match policy.middle(update)? {
+            Some(page) => result.sum += page,
+            None => result.skipped.push(update.clone()),
+        }
+    }
+    Ok(result)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     fn test_parse_input() {
@@ -123,7 +423,8 @@ mod tests {
 75,97,47,61,53
 61,13,29
 97,13,75,29,47";
-        let (rules, updates) = parse_input(input);
+        let (rules, updates) = parse_input(input).unwrap();
+        let precedence = Precedence::new(&rules);
         assert_eq!(rules.len(), 6);
         assert_eq!(rules[&47], vec![53, 13, 61, 29]);
         assert_eq!(rules[&53], vec![29, 13]);
@@ -131,27 +432,32 @@ mod tests {
         assert_eq!(updates.len(), 6);
         assert_eq!(updates[0], vec![75, 47, 61, 53, 29]);
 
-        let sum = sum_middle_value(&updates);
+        let sum = sum_middle_value(&updates, MiddlePolicy::Error).unwrap().sum;
         assert_eq!(sum, 61 + 53 + 13 + 47 + 13 + 75);
 
         let want = vec![75, 29, 13];
-        let got = order_updates(&updates[2], &rules);
+        let got = order_updates(&updates[2], &precedence).unwrap();
         assert_eq!(got, want);
 
         let want = vec![97, 75, 47, 61, 53];
-        let got = order_updates(&updates[3], &rules);
+        let got = order_updates(&updates[3], &precedence).unwrap();
         assert_eq!(got, want);
 
         let want = vec![61, 29, 13];
-        let got = order_updates(&updates[4], &rules);
+        let got = order_updates(&updates[4], &precedence).unwrap();
         assert_eq!(got, want);
 
         let want = vec![97, 75, 47, 29, 13];
-        let got = order_updates(&updates[5], &rules);
+        let got = order_updates(&updates[5], &precedence).unwrap();
         assert_eq!(got, want);
 
         let want = 61;
-        let got = sum_middle_value(&[order_updates(&updates[0], &rules)]);
+        let got = sum_middle_value(
+            &[order_updates(&updates[0], &precedence).unwrap()],
+            MiddlePolicy::Error,
+        )
+        .unwrap()
+        .sum;
         assert_eq!(got, want);
 
         let want = 143;
@@ -159,10 +465,368 @@ mod tests {
             &updates
                 .iter()
                 .take(3)
-                .map(|u| order_updates(u, &rules))
+                .map(|u| order_updates(u, &precedence).unwrap())
                 .collect::<Vec<_>>(),
-        );
+            MiddlePolicy::Error,
+        )
+        .unwrap()
+        .sum;
 
         assert_eq!(got, want);
     }
+
+    #[test]
+    fn test_order_updates_cycle() {
+        let rules: HashMap<i32, Vec<i32>> =
+            HashMap::from([(1, vec![2]), (2, vec![3]), (3, vec![1]), (4, vec![1])]);
+        let precedence = Precedence::new(&rules);
+
+        let got = order_updates(&[4, 1, 2, 3], &precedence);
+        let err = got.unwrap_err();
+        assert_eq!(err.pages.len(), 3);
+        for pair in err.pages.windows(2) {
+            assert!(rules[&pair[0]].contains(&pair[1]));
+        }
+        assert!(rules[&err.pages[2]].contains(&err.pages[0]));
+        assert!(err.to_string().starts_with("Rules contain a cycle: "));
+
+        assert_eq!(order_updates(&[3, 1], &precedence), Ok(vec![3, 1]));
+        assert_eq!(order_updates(&[2, 4, 1], &precedence), Ok(vec![4, 1, 2]));
+    }
+
+    #[test]
+    fn test_order_updates_unrelated_pages() {
+        let rules: HashMap<i32, Vec<i32>> = HashMap::from([(5, vec![1]), (1, vec![9])]);
+        let precedence = Precedence::new(&rules);
+
+        // Only rules between pages of the update apply, 1 is not part of it.
+        assert_eq!(
+            order_updates(&[9, 7, 5, 3], &precedence),
+            Ok(vec![9, 7, 5, 3])
+        );
+        assert_eq!(
+            order_updates(&[9, 3, 1, 5], &precedence),
+            Ok(vec![3, 5, 1, 9])
+        );
+    }
+
+    #[test]
+    fn test_validate_update() {
+        let rules: HashMap<i32, Vec<i32>> = HashMap::from([
+            (47, vec![53, 13, 61, 29]),
+            (97, vec![13, 61, 47, 29, 53, 75]),
+            (75, vec![29, 53, 47, 61, 13]),
+            (61, vec![13, 53, 29]),
+            (29, vec![13]),
+            (53, vec![29, 13]),
+        ]);
+        let precedence = Precedence::new(&rules);
+
+        assert!(validate_update(&[75, 47, 61, 53, 29], &rules).is_empty());
+
+        let got = validate_update(&[75, 97, 47, 61, 53], &rules);
+        assert_eq!(
+            got,
+            vec![Violation {
+                before: 97,
+                after: 75,
+                before_position: 1,
+                after_position: 0,
+            }]
+        );
+        assert_eq!(
+            got[0].to_string(),
+            "rule 97|75 violated: 97 at position 1 comes after 75 at position 0"
+        );
+
+        let got = validate_update(&[97, 13, 75, 29, 47], &rules);
+        let broken: Vec<(i32, i32)> = got.iter().map(|v| (v.before, v.after)).collect();
+        assert_eq!(broken, vec![(75, 13), (29, 13), (47, 13), (47, 29)]);
+
+        for update in [[75, 97, 47, 61, 53], [97, 13, 75, 29, 47]] {
+            let ordered = order_updates(&update, &precedence).unwrap();
+            assert!(validate_update(&ordered, &rules).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let rules: HashMap<i32, Vec<i32>> = HashMap::from([
+            (97, vec![75, 47, 13]),
+            (75, vec![47, 13]),
+            (47, vec![13]),
+            (29, vec![13]),
+        ]);
+
+        let got = to_dot(&rules, &DotOptions::default());
+        assert!(got.starts_with("digraph rules {\n    13;\n    29;\n"));
+        assert!(got.contains("    29 -> 13;\n"));
+        assert_eq!(got.matches("->").count(), 7);
+
+        let update = [75, 97, 47, 13];
+        let got = to_dot(
+            &rules,
+            &DotOptions {
+                restrict_to: Some(&update),
+                highlight: Some(&update),
+                transitive_reduction: true,
+            },
+        );
+        assert_eq!(
+            got,
+            "digraph rules {
+    13;
+    47;
+    75;
+    97;
+    47 -> 13;
+    75 -> 47;
+    97 -> 75 [color=red, penwidth=2];
+}
+"
+        );
+
+        let cyclic: HashMap<i32, Vec<i32>> =
+            HashMap::from([(1, vec![2, 3]), (2, vec![3]), (3, vec![1])]);
+        let got = to_dot(
+            &cyclic,
+            &DotOptions {
+                transitive_reduction: true,
+                ..DotOptions::default()
+            },
+        );
+        assert_eq!(
+            got,
+            "digraph rules {
+    1;
+    2;
+    3;
+    1 -> 2;
+    2 -> 3;
+    3 -> 1;
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_parse_input_robust() {
+        let input = "\r\n47|53\r\n97|13 \r\n\r\n75,47,53\r\n97,13\r\n\r\n  \r\n";
+        let (rules, updates) = parse_input(input).unwrap();
+        assert_eq!(rules[&47], vec![53]);
+        assert_eq!(rules[&97], vec![13]);
+        assert_eq!(updates, vec![vec![75, 47, 53], vec![97, 13]]);
+
+        let (rules, updates) = parse_input("47|53\n").unwrap();
+        assert_eq!(rules.len(), 1);
+        assert!(updates.is_empty());
+
+        let located = |line: usize, column: usize, expected: &str, text: &str| parse::ParseError {
+            line,
+            column,
+            expected: expected.to_string(),
+            text: text.to_string(),
+        };
+        assert_eq!(
+            parse_input("47|53\n47-53\n\n1,2"),
+            Err(InputError::InvalidRule(located(2, 3, "'|'", "47-53")))
+        );
+        assert_eq!(
+            parse_input("47|53|61\n\n1,2"),
+            Err(InputError::InvalidRule(located(
+                1,
+                6,
+                "end of line",
+                "47|53|61"
+            )))
+        );
+        assert_eq!(
+            parse_input("47|53\n\n1,2\n1,,2\n"),
+            Err(InputError::InvalidUpdate(located(
+                4,
+                3,
+                "unsigned integer",
+                "1,,2"
+            )))
+        );
+        assert_eq!(
+            parse_input("47|53\n\n1,99999999999"),
+            Err(InputError::InvalidUpdate(located(
+                3,
+                3,
+                "unsigned integer",
+                "1,99999999999"
+            )))
+        );
+        assert_eq!(
+            parse_input("47|53\n75,47\n").unwrap_err().to_string(),
+            "Invalid rule on line 2, column 3: expected '|' in '75,47'. Rules look like '47|53'"
+        );
+    }
+
+    #[test]
+    fn test_precedence() {
+        let rules: HashMap<i32, Vec<i32>> =
+            HashMap::from([(47, vec![53, 13]), (97, vec![47]), (13, vec![97])]);
+        let precedence = Precedence::new(&rules);
+        assert_eq!(precedence.len(), 4);
+
+        for a in [47, 53, 13, 97, 1] {
+            for b in [47, 53, 13, 97, 1] {
+                let want = rules.get(&a).is_some_and(|after| after.contains(&b));
+                assert_eq!(precedence.before(a, b), want, "{a}|{b}");
+            }
+        }
+        assert!(Precedence::new(&HashMap::new()).is_empty());
+
+        for update in [vec![53, 47, 97], vec![1, 13, 47, 53], vec![13, 97, 47]] {
+            assert_eq!(
+                order_updates(&update, &precedence),
+                order_updates_naive(&update, &rules)
+            );
+        }
+    }
+
+    #[test]
+    fn test_repair() {
+        let rules: HashMap<i32, Vec<i32>> = HashMap::from([
+            (47, vec![53, 13, 61, 29]),
+            (97, vec![13, 61, 47, 29, 53, 75]),
+            (75, vec![29, 53, 47, 61, 13]),
+            (61, vec![13, 53, 29]),
+            (29, vec![13]),
+            (53, vec![29, 13]),
+        ]);
+        let precedence = Precedence::new(&rules);
+
+        assert_eq!(repair::min_moves(&[75, 47, 61, 53, 29], &precedence), 0);
+        assert_eq!(repair::min_moves(&[75, 97, 47, 61, 53], &precedence), 1);
+        assert_eq!(repair::min_moves(&[61, 13, 29], &precedence), 1);
+        assert_eq!(repair::min_moves(&[97, 13, 75, 29, 47], &precedence), 2);
+        assert_eq!(
+            repair::count_orderings(&[97, 13, 75, 29, 47], &precedence),
+            Some(1)
+        );
+
+        // 5 and 7 are unrelated to each other, 3 has to come first.
+        let rules: HashMap<i32, Vec<i32>> = HashMap::from([(3, vec![5, 7])]);
+        let precedence = Precedence::new(&rules);
+        assert_eq!(repair::min_moves(&[7, 5, 3], &precedence), 1);
+        assert_eq!(repair::count_orderings(&[7, 5, 3], &precedence), Some(2));
+        assert_eq!(
+            repair::orderings(&[7, 5, 3], &precedence, 10),
+            vec![vec![3, 7, 5], vec![3, 5, 7]]
+        );
+        assert_eq!(
+            repair::orderings(&[7, 5, 3], &precedence, 1),
+            vec![vec![3, 7, 5]]
+        );
+        assert_eq!(
+            repair::count_orderings(&(10..22).collect::<Vec<_>>(), &precedence),
+            Some((1..=12u128).product())
+        );
+        assert_eq!(repair::count_orderings(&[], &precedence), Some(1));
+        // 2^20 sets of placed pages, too many to remember.
+        assert_eq!(
+            repair::count_orderings(&(10..30).collect::<Vec<_>>(), &precedence),
+            None
+        );
+        assert_eq!(
+            repair::count_orderings(&(0..129).collect::<Vec<_>>(), &precedence),
+            None
+        );
+
+        let rules: HashMap<i32, Vec<i32>> = HashMap::from([(1, vec![2]), (2, vec![1])]);
+        let precedence = Precedence::new(&rules);
+        assert!(repair::orderings(&[1, 2], &precedence, 1).is_empty());
+        assert_eq!(repair::count_orderings(&[1, 2], &precedence), Some(0));
+    }
+
+    /// The length of the longest common subsequence of two permutations.
+    fn common_subsequence(a: &[i32], b: &[i32]) -> usize {
+        let mut lengths = vec![vec![0; b.len() + 1]; a.len() + 1];
+        for (i, x) in a.iter().enumerate() {
+            for (j, y) in b.iter().enumerate() {
+                lengths[i + 1][j + 1] = if x == y {
+                    lengths[i][j] + 1
+                } else {
+                    lengths[i][j + 1].max(lengths[i + 1][j])
+                };
+            }
+        }
+        lengths[a.len()][b.len()]
+    }
+
+    proptest! {
+        #[test]
+        fn test_repair_matches_brute_force(
+            update in Just((0..7).collect::<Vec<i32>>())
+                .prop_shuffle(),
+            edges in proptest::collection::vec(prop::bool::weighted(0.3), 21),
+        ) {
+            // Rules only point from smaller to larger pages, so they never form a cycle.
+            let mut rules: HashMap<i32, Vec<i32>> = HashMap::new();
+            let pairs = (0..7).flat_map(|a| (a + 1..7).map(move |b| (a, b)));
+            for ((a, b), edge) in pairs.zip(edges) {
+                if edge {
+                    rules.entry(a).or_default().push(b);
+                }
+            }
+            let precedence = Precedence::new(&rules);
+
+            let all = repair::orderings(&update, &precedence, usize::MAX);
+            let kept = all
+                .iter()
+                .map(|ordering| common_subsequence(&update, ordering))
+                .max()
+                .unwrap();
+            prop_assert_eq!(
+                repair::min_moves(&update, &precedence),
+                update.len() - kept
+            );
+            prop_assert_eq!(
+                repair::count_orderings(&update, &precedence),
+                Some(all.len() as u128)
+            );
+            prop_assert_eq!(&all[0], &order_updates(&update, &precedence).unwrap());
+            for ordering in &all {
+                prop_assert!(validate_update(ordering, &rules).is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_middle_policy() {
+        let updates = vec![vec![1, 2, 3], vec![4, 8], vec![]];
+
+        let err = sum_middle_value(&updates, MiddlePolicy::Error).unwrap_err();
+        assert_eq!(err.update, vec![4, 8]);
+        assert_eq!(
+            err.to_string(),
+            "Update 4,8 has 2 pages and no single middle page"
+        );
+
+        let got = sum_middle_value(&updates, MiddlePolicy::Skip).unwrap();
+        assert_eq!(got.sum, 2);
+        assert_eq!(got.skipped, vec![vec![4, 8], vec![]]);
+
+        let even = &updates[..2];
+        let pick = |policy| sum_middle_value(even, policy).unwrap();
+        assert_eq!(pick(MiddlePolicy::Lower).sum, 2 + 4);
+        assert_eq!(pick(MiddlePolicy::Upper).sum, 2 + 8);
+        assert_eq!(pick(MiddlePolicy::Average).sum, 2 + 6);
+        assert!(pick(MiddlePolicy::Average).skipped.is_empty());
+        assert_eq!(MiddlePolicy::Average.middle(&[1, 4]), Ok(Some(2)));
+
+        assert!(sum_middle_value(&updates, MiddlePolicy::Lower).is_err());
+        assert_eq!(
+            MiddlePolicy::Upper.middle(&[]).unwrap_err().to_string(),
+            "Update is empty and has no middle page"
+        );
+
+        for policy in ["error", "skip", "lower", "upper", "average"] {
+            assert_eq!(policy.parse::<MiddlePolicy>().unwrap().to_string(), policy);
+        }
+        assert!("middle".parse::<MiddlePolicy>().is_err());
+    }
 }
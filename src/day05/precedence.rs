@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+
+/// The ordering rules as a dense adjacency bitset, so whether one page has to
+/// come before another is answered in constant time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Precedence {
+    index: HashMap<i32, usize>,
+    bits: Vec<u64>,
+}
+
+impl Precedence {
+    /// Builds the relation from rules keyed by the page that has to come first.
+    pub fn new(rules: &HashMap<i32, Vec<i32>>) -> Self {
+        let mut index = HashMap::new();
+        for (before, after) in rules {
+            for page in std::iter::once(before).chain(after) {
+                let next = index.len();
+                index.entry(*page).or_insert(next);
+            }
+        }
+
+        let pages = index.len();
+        let mut bits = vec![0; (pages * pages).div_ceil(64)];
+        for (before, after) in rules {
+            for page in after {
+                let bit = index[before] * pages + index[page];
+                bits[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+
+        Self { index, bits }
+    }
+
+    /// The number of distinct pages mentioned by the rules.
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// The dense index of `page`, if any rule mentions it.
+    pub fn index(&self, page: i32) -> Option<usize> {
+        self.index.get(&page).copied()
+    }
+
+    /// Whether a rule requires `a` to come before `b`.
+    pub fn before(&self, a: i32, b: i32) -> bool {
+        match (self.index(a), self.index(b)) {
+            (Some(a), Some(b)) => self.before_index(a, b),
+            _ => false,
+        }
+    }
+
+    /// Same as [`Self::before`] for pages already resolved with [`Self::index`].
+    pub fn before_index(&self, a: usize, b: usize) -> bool {
+        let bit = a * self.index.len() + b;
+        self.bits[bit / 64] & (1 << (bit % 64)) != 0
+    }
+}
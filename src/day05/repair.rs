@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+use super::precedence::Precedence;
+
+/// The maximum number of pages [`count_orderings`] accepts, one bit per page.
+pub const MAX_COUNTED_PAGES: usize = u128::BITS as usize;
+
+/// The maximum number of sets of placed pages [`count_orderings`] remembers
+/// before giving up.
+pub const MAX_COUNTED_STATES: usize = 1 << 16;
+
+/// The minimum number of pages that have to be moved to another position to
+/// correctly order `update`.
+///
+/// The pages left in place keep their relative order, so no rule, directly or
+/// through other pages of the update, may require a later one of them before an
+/// earlier one. The largest such set is found as the complement of a maximum
+/// matching between pages and the earlier pages they have to precede.
+///
+/// The rules between the pages must not contain a cycle, which
+/// [`super::order_updates`] checks.
+pub fn min_moves(update: &[i32], precedence: &Precedence) -> usize {
+    let reach = reachability(update, precedence);
+
+    let mut matched = vec![None; update.len()];
+    let mut moves = 0;
+    for later in 0..update.len() {
+        let mut seen = vec![false; update.len()];
+        if augment(later, &reach, &mut matched, &mut seen) {
+            moves += 1;
+        }
+    }
+    moves
+}
+
+/// Tries to match `later` to an earlier page it has to precede, re-matching
+/// other pages along an augmenting path if needed.
+fn augment(
+    later: usize,
+    reach: &[Vec<bool>],
+    matched: &mut [Option<usize>],
+    seen: &mut [bool],
+) -> bool {
+    for earlier in 0..later {
+        if reach[later][earlier] && !seen[earlier] {
+            seen[earlier] = true;
+            if matched[earlier].is_none_or(|other| augment(other, reach, matched, seen)) {
+                matched[earlier] = Some(later);
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Whether the page at one position has to come before the page at another,
+/// directly or through other pages of `update`.
+fn reachability(update: &[i32], precedence: &Precedence) -> Vec<Vec<bool>> {
+    let mut reach: Vec<Vec<bool>> = update
+        .iter()
+        .map(|&a| update.iter().map(|&b| precedence.before(a, b)).collect())
+        .collect();
+    for via in 0..update.len() {
+        let successors = reach[via].clone();
+        for row in &mut reach {
+            if row[via] {
+                for (reachable, &successor) in row.iter_mut().zip(&successors) {
+                    *reachable |= successor;
+                }
+            }
+        }
+    }
+    reach
+}
+
+/// Enumerates the correct orderings of the pages of `update`, at most `limit` of them.
+///
+/// Orderings are produced by always trying the available pages in the order
+/// they appear in `update`, so the first one equals [`super::order_updates`].
+/// If the rules between the pages contain a cycle, there are none.
+pub fn orderings(update: &[i32], precedence: &Precedence, limit: usize) -> Vec<Vec<i32>> {
+    let mut found = Vec::new();
+    let mut placed = vec![false; update.len()];
+    let mut ordering = Vec::with_capacity(update.len());
+    enumerate(
+        update,
+        precedence,
+        limit,
+        &mut placed,
+        &mut ordering,
+        &mut found,
+    );
+    found
+}
+
+fn enumerate(
+    update: &[i32],
+    precedence: &Precedence,
+    limit: usize,
+    placed: &mut [bool],
+    ordering: &mut Vec<i32>,
+    found: &mut Vec<Vec<i32>>,
+) {
+    if ordering.len() == update.len() {
+        found.push(ordering.clone());
+        return;
+    }
+    for next in 0..update.len() {
+        if found.len() >= limit {
+            return;
+        }
+        if !is_available(update, precedence, placed, next) {
+            continue;
+        }
+        placed[next] = true;
+        ordering.push(update[next]);
+        enumerate(update, precedence, limit, placed, ordering, found);
+        ordering.pop();
+        placed[next] = false;
+    }
+}
+
+/// Counts the correct orderings of the pages of `update`, saturating at `u128::MAX`.
+///
+/// Every set of already placed pages is counted only once, so updates whose
+/// rules leave little freedom are counted quickly even if they are long. If the
+/// rules between the pages contain a cycle, there are no orderings.
+///
+/// Returns `None` if the update has more than [`MAX_COUNTED_PAGES`] pages or
+/// the rules leave so much freedom that more than [`MAX_COUNTED_STATES`] sets
+/// of placed pages would have to be counted.
+pub fn count_orderings(update: &[i32], precedence: &Precedence) -> Option<u128> {
+    if update.len() > MAX_COUNTED_PAGES {
+        return None;
+    }
+
+    // The pages each page requires before it, one bit per position in `update`.
+    let required: Vec<u128> = update
+        .iter()
+        .map(|&page| {
+            update
+                .iter()
+                .enumerate()
+                .filter(|(_, other)| precedence.before(**other, page))
+                .fold(0, |mask, (index, _)| mask | 1 << index)
+        })
+        .collect();
+    let all = u128::MAX
+        .checked_shr((MAX_COUNTED_PAGES - update.len()) as u32)
+        .unwrap_or_default();
+
+    count(&required, all, 0, &mut HashMap::new())
+}
+
+fn count(
+    required: &[u128],
+    all: u128,
+    placed: u128,
+    memo: &mut HashMap<u128, u128>,
+) -> Option<u128> {
+    if placed == all {
+        return Some(1);
+    }
+    if let Some(&known) = memo.get(&placed) {
+        return Some(known);
+    }
+    if memo.len() >= MAX_COUNTED_STATES {
+        return None;
+    }
+
+    let mut total: u128 = 0;
+    for (next, &before) in required.iter().enumerate() {
+        if placed & 1 << next == 0 && before & !placed == 0 {
+            total = total.saturating_add(count(required, all, placed | 1 << next, memo)?);
+        }
+    }
+    memo.insert(placed, total);
+    Some(total)
+}
+
+/// Whether the page at `next` is not placed yet and all pages it requires
+/// before it are.
+fn is_available(update: &[i32], precedence: &Precedence, placed: &[bool], next: usize) -> bool {
+    !placed[next]
+        && update
+            .iter()
+            .zip(placed)
+            .enumerate()
+            .all(|(other, (&page, &placed))| {
+                placed || other == next || !precedence.before(page, update[next])
+            })
+}
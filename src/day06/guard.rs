@@ -1,34 +1,32 @@
 use std::collections::HashSet;
 
+use crate::geometry::{Direction4, Point};
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Guard {
-    x: usize,
-    y: usize,
-    direction: Direction,
-    visited: HashSet<(usize, usize)>,
+    position: Point,
+    direction: Direction4,
+    visited: HashSet<Point>,
 }
 
 impl Guard {
-    #[allow(dead_code)]
-    pub fn new(x: usize, y: usize, direction: Direction) -> Self {
+    pub fn new(position: Point, direction: Direction4) -> Self {
         let mut visited = HashSet::new();
-        visited.insert((x, y));
+        visited.insert(position);
         Self {
-            x,
-            y,
+            position,
             direction,
             visited,
         }
     }
 
     #[allow(dead_code)]
-    pub const fn direction(&self) -> &Direction {
-        &self.direction
+    pub const fn direction(&self) -> Direction4 {
+        self.direction
     }
 
-    #[allow(dead_code)]
-    pub const fn start(&self) -> (usize, usize) {
-        (self.x, self.y)
+    pub const fn position(&self) -> Point {
+        self.position
     }
 
     #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
@@ -36,51 +34,17 @@ impl Guard {
         self.visited.len() as i32
     }
 
-    #[allow(dead_code)]
-    pub const fn next_move(&self) -> (usize, usize) {
-        match self.direction {
-            Direction::Up => (self.x - 1, self.y),
-            Direction::Down => (self.x + 1, self.y),
-            Direction::Left => (self.x, self.y - 1),
-            Direction::Right => (self.x, self.y + 1),
-            Direction::Unknown => (self.x, self.y),
-        }
+    /// The cell the guard would step onto next.
+    pub fn next_move(&self) -> Point {
+        self.position + self.direction.delta()
     }
 
-    #[allow(dead_code)]
-    pub fn move_guard(&mut self, direction: &Direction) {
-        match direction {
-            Direction::Up => self.x -= 1,
-            Direction::Down => self.x += 1,
-            Direction::Left => self.y -= 1,
-            Direction::Right => self.y += 1,
-            Direction::Unknown => {
-                return;
-            }
-        }
-        self.direction = direction.clone();
-        self.visited.insert((self.x, self.y));
-        println!("Moved to: x: {} | y: {}", self.x, self.y);
+    pub const fn turn_right(&mut self) {
+        self.direction = self.direction.turn_right();
     }
-}
-
-#[derive(Debug, PartialEq, Eq, Clone)]
-pub enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-    Unknown,
-}
 
-impl From<char> for Direction {
-    fn from(c: char) -> Self {
-        match c {
-            '^' => Self::Up,
-            'v' => Self::Down,
-            '<' => Self::Left,
-            '>' => Self::Right,
-            _ => Self::Unknown,
-        }
+    pub fn move_forward(&mut self) {
+        self.position = self.next_move();
+        self.visited.insert(self.position);
     }
 }
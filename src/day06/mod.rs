@@ -2,6 +2,12 @@ use std::{error::Error, fs};
 
 use guard::Guard;
 
+use crate::{
+    geometry::{Direction4, Point},
+    grid::Grid,
+    parse::char_grid,
+};
+
 mod guard;
 
 /// Solves the problem for day 06.
@@ -13,8 +19,8 @@ mod guard;
 pub fn solve_day06(path: &str) -> Result<(i32, i32), Box<dyn Error>> {
     let content = fs::read_to_string(path)?;
 
-    let (mut guard, map) = parse_input(&content);
-    let part_two = find_possible_obstacles(guard.start(), &map);
+    let (mut guard, map) = parse_input(&content)?;
+    let part_two = find_possible_obstacles(guard.position(), &map);
     calc_guard_path(&mut guard, &map);
     let part_one = guard.visited();
 
@@ -26,63 +32,38 @@ pub fn solve_day06(path: &str) -> Result<(i32, i32), Box<dyn Error>> {
     clippy::cast_possible_truncation,
     clippy::cast_possible_wrap
 )]
-fn find_possible_obstacles(_start: (usize, usize), _map: &[Vec<char>]) -> i32 {
-    let mut obstacles: Vec<(usize, usize)> = vec![];
+fn find_possible_obstacles(_start: Point, _map: &Grid<char>) -> i32 {
+    let mut obstacles: Vec<Point> = vec![];
 
     obstacles.len() as i32
 }
 
-fn calc_guard_path(guard: &mut Guard, map: &[Vec<char>]) {
-    loop {
-        let (x, y) = guard.next_move();
-        if x >= map.len() || y >= map[0].len() {
-            break;
-        }
-        // dbg!(x, y);
-        let c = map[x][y];
+fn calc_guard_path(guard: &mut Guard, map: &Grid<char>) {
+    while let Some(&c) = map.get(guard.next_move()) {
         if c == '#' {
-            let new_direction = match guard.direction() {
-                guard::Direction::Up => &guard::Direction::Right,
-                guard::Direction::Down => &guard::Direction::Left,
-                guard::Direction::Left => &guard::Direction::Up,
-                guard::Direction::Right => &guard::Direction::Down,
-                guard::Direction::Unknown => &guard::Direction::Unknown,
-            };
-            guard.move_guard(new_direction);
+            guard.turn_right();
         } else {
-            let direction = guard.direction().clone();
-            guard.move_guard(&direction);
+            guard.move_forward();
         }
     }
 }
 
-fn parse_input(input: &str) -> (guard::Guard, Vec<Vec<char>>) {
-    let mut guard = guard::Guard::new(0, 0, guard::Direction::Unknown);
-    let map = input
-        .split('\n')
-        .enumerate()
-        .flat_map(|(i, r)| {
-            if r.contains('^') || r.contains('v') || r.contains('<') || r.contains('>') {
-                let () = r
-                    .chars()
-                    .enumerate()
-                    .filter(|(_, c)| *c == '^' || *c == 'v' || *c == '<' || *c == '>')
-                    .map(|(j, c)| {
-                        let d = guard::Direction::from(c);
-                        guard = guard::Guard::new(i, j, d);
-                    })
-                    .collect::<()>();
-            }
-            vec![r.chars().collect()]
-        })
-        .collect();
-    (guard, map)
+fn parse_input(input: &str) -> Result<(Guard, Grid<char>), Box<dyn Error>> {
+    let map = char_grid(input)?;
+    let guard = map
+        .iter()
+        .find_map(|(point, c)| Some(Guard::new(point, Direction4::try_from(*c).ok()?)))
+        .ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Invalid map. No guard (^, >, v or <) found",
+            )
+        })?;
+    Ok((guard, map))
 }
 
 #[cfg(test)]
 mod tests {
-    use guard::Guard;
-
     use super::*;
 
     #[test]
@@ -98,12 +79,17 @@ mod tests {
 #.........
 ......#^..";
 
-        let (mut guard, map) = parse_input(input);
-        assert_eq!(guard, Guard::new(9, 7, guard::Direction::Up));
+        let (mut guard, map) = parse_input(input).unwrap();
+        assert_eq!(guard, Guard::new(Point::new(7, 9), Direction4::Up));
 
         calc_guard_path(&mut guard, &map);
         let want = 7;
         let got = guard.visited();
         assert_eq!(got, want);
     }
+
+    #[test]
+    fn test_parse_input_without_guard() {
+        assert!(parse_input("..#\n...").is_err());
+    }
 }
@@ -0,0 +1,267 @@
+use std::ops::{Add, Sub};
+
+/// A cell position, `x` counting columns from the left and `y` rows from the top.
+///
+/// Coordinates are signed, so stepping off the top or left edge yields a point
+/// that is simply not part of any grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Point {
+    pub x: isize,
+    pub y: isize,
+}
+
+impl Point {
+    pub const fn new(x: isize, y: isize) -> Self {
+        Self { x, y }
+    }
+
+    /// The point `dx` columns and `dy` rows away.
+    #[must_use]
+    pub const fn offset(self, dx: isize, dy: isize) -> Self {
+        Self::new(self.x + dx, self.y + dy)
+    }
+
+    /// `self + delta`, if it lies within a `width` by `height` grid.
+    pub fn checked_add(self, delta: Self, width: usize, height: usize) -> Option<Self> {
+        let x = self.x.checked_add(delta.x)?;
+        let y = self.y.checked_add(delta.y)?;
+        let inside = |value: isize, bound: usize| usize::try_from(value).is_ok_and(|v| v < bound);
+        (inside(x, width) && inside(y, height)).then_some(Self::new(x, y))
+    }
+
+    /// The number of orthogonal steps between `self` and `other`.
+    pub const fn manhattan(self, other: Self) -> usize {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    /// The number of steps between `self` and `other` if diagonal steps are allowed.
+    pub const fn chebyshev(self, other: Self) -> usize {
+        let (dx, dy) = (self.x.abs_diff(other.x), self.y.abs_diff(other.y));
+        if dx > dy { dx } else { dy }
+    }
+}
+
+impl Add for Point {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        self.offset(other.x, other.y)
+    }
+}
+
+impl Sub for Point {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self.offset(-other.x, -other.y)
+    }
+}
+
+/// One of the four orthogonal directions, with up pointing to row 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction4 {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction4 {
+    /// All directions in clockwise order, starting with [`Self::Up`].
+    pub const ALL: [Self; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
+
+    /// The direction after turning 90 degrees clockwise.
+    #[must_use]
+    pub const fn turn_right(self) -> Self {
+        match self {
+            Self::Up => Self::Right,
+            Self::Right => Self::Down,
+            Self::Down => Self::Left,
+            Self::Left => Self::Up,
+        }
+    }
+
+    /// The direction after turning 90 degrees counterclockwise.
+    #[must_use]
+    pub const fn turn_left(self) -> Self {
+        self.opposite().turn_right()
+    }
+
+    #[must_use]
+    pub const fn opposite(self) -> Self {
+        self.turn_right().turn_right()
+    }
+
+    /// The offset of a single step in this direction.
+    pub const fn delta(self) -> Point {
+        match self {
+            Self::Up => Point::new(0, -1),
+            Self::Right => Point::new(1, 0),
+            Self::Down => Point::new(0, 1),
+            Self::Left => Point::new(-1, 0),
+        }
+    }
+}
+
+impl TryFrom<char> for Direction4 {
+    type Error = std::io::Error;
+
+    /// Parses an arrow as drawn in a map: `^`, `>`, `v` or `<`.
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '^' => Ok(Self::Up),
+            '>' => Ok(Self::Right),
+            'v' => Ok(Self::Down),
+            '<' => Ok(Self::Left),
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Invalid direction '{c}'. Expected one of: ^, >, v, <"),
+            )),
+        }
+    }
+}
+
+/// One of the eight compass directions, with north pointing to row 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction8 {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction8 {
+    pub const ORTHOGONAL: [Self; 4] = [Self::North, Self::East, Self::South, Self::West];
+    pub const DIAGONAL: [Self; 4] = [
+        Self::NorthEast,
+        Self::SouthEast,
+        Self::SouthWest,
+        Self::NorthWest,
+    ];
+    /// All directions in clockwise order, starting with [`Self::North`].
+    pub const ALL: [Self; 8] = [
+        Self::North,
+        Self::NorthEast,
+        Self::East,
+        Self::SouthEast,
+        Self::South,
+        Self::SouthWest,
+        Self::West,
+        Self::NorthWest,
+    ];
+
+    const fn index(self) -> usize {
+        match self {
+            Self::North => 0,
+            Self::NorthEast => 1,
+            Self::East => 2,
+            Self::SouthEast => 3,
+            Self::South => 4,
+            Self::SouthWest => 5,
+            Self::West => 6,
+            Self::NorthWest => 7,
+        }
+    }
+
+    /// The direction after turning 45 degrees clockwise.
+    #[must_use]
+    pub const fn turn_right(self) -> Self {
+        Self::ALL[(self.index() + 1) % 8]
+    }
+
+    /// The direction after turning 45 degrees counterclockwise.
+    #[must_use]
+    pub const fn turn_left(self) -> Self {
+        Self::ALL[(self.index() + 7) % 8]
+    }
+
+    #[must_use]
+    pub const fn opposite(self) -> Self {
+        Self::ALL[(self.index() + 4) % 8]
+    }
+
+    /// The offset of a single step in this direction.
+    pub const fn delta(self) -> Point {
+        match self {
+            Self::North => Point::new(0, -1),
+            Self::NorthEast => Point::new(1, -1),
+            Self::East => Point::new(1, 0),
+            Self::SouthEast => Point::new(1, 1),
+            Self::South => Point::new(0, 1),
+            Self::SouthWest => Point::new(-1, 1),
+            Self::West => Point::new(-1, 0),
+            Self::NorthWest => Point::new(-1, -1),
+        }
+    }
+}
+
+impl From<Direction4> for Direction8 {
+    fn from(direction: Direction4) -> Self {
+        match direction {
+            Direction4::Up => Self::North,
+            Direction4::Right => Self::East,
+            Direction4::Down => Self::South,
+            Direction4::Left => Self::West,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_point() {
+        let a = Point::new(1, 2);
+        let b = Point::new(-3, 5);
+        assert_eq!(a + b, Point::new(-2, 7));
+        assert_eq!(a - b, Point::new(4, -3));
+        assert_eq!(a.manhattan(b), 7);
+        assert_eq!(a.chebyshev(b), 4);
+        assert_eq!(b.chebyshev(b), 0);
+
+        assert_eq!(
+            a.checked_add(Point::new(1, 0), 3, 3),
+            Some(Point::new(2, 2))
+        );
+        assert_eq!(a.checked_add(Point::new(2, 0), 3, 3), None);
+        assert_eq!(a.checked_add(Point::new(0, -3), 3, 3), None);
+        assert_eq!(
+            Point::new(isize::MAX, 0).checked_add(Point::new(1, 0), 3, 3),
+            None
+        );
+    }
+
+    #[test]
+    fn test_directions() {
+        for direction in Direction4::ALL {
+            assert_eq!(direction.turn_right().turn_left(), direction);
+            assert_eq!(direction.opposite().opposite(), direction);
+            assert_eq!(
+                direction.delta() + direction.opposite().delta(),
+                Point::default()
+            );
+            assert_eq!(Direction8::from(direction).delta(), direction.delta());
+        }
+        assert_eq!(Direction4::Up.turn_right(), Direction4::Right);
+        assert_eq!(Direction4::Up.turn_left(), Direction4::Left);
+        assert_eq!(Direction4::try_from('v').unwrap(), Direction4::Down);
+        assert!(Direction4::try_from('x').is_err());
+
+        for direction in Direction8::ALL {
+            assert_eq!(direction.turn_right().turn_left(), direction);
+            assert_eq!(
+                direction.delta() + direction.opposite().delta(),
+                Point::default()
+            );
+            assert_eq!(direction.delta().chebyshev(Point::default()), 1);
+        }
+        assert_eq!(Direction8::NorthWest.turn_right(), Direction8::North);
+        assert_eq!(Direction8::North.turn_left(), Direction8::NorthWest);
+        assert_eq!(Direction8::East.opposite(), Direction8::West);
+    }
+}
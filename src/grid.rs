@@ -0,0 +1,328 @@
+use std::{
+    error::Error,
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+use crate::geometry::{Direction4, Direction8, Point};
+
+/// A row of a grid that differs in length from the first one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShapeError {
+    pub line: usize,
+    pub expected: usize,
+    pub found: usize,
+}
+
+impl fmt::Display for ShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid grid. Line {} has {} cells, expected {}",
+            self.line, self.found, self.expected
+        )
+    }
+}
+
+impl Error for ShapeError {}
+
+/// A rectangular grid stored row-major in one contiguous buffer.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Default for Grid<T> {
+    fn default() -> Self {
+        Self {
+            cells: Vec::new(),
+            width: 0,
+            height: 0,
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    /// Builds a grid from its rows.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a row differs in length from the first one.
+    pub fn from_rows(rows: impl IntoIterator<Item = Vec<T>>) -> Result<Self, ShapeError> {
+        let mut cells = Vec::new();
+        let mut width = 0;
+        let mut height = 0;
+        for row in rows {
+            if height == 0 {
+                width = row.len();
+            } else if row.len() != width {
+                return Err(ShapeError {
+                    line: height + 1,
+                    expected: width,
+                    found: row.len(),
+                });
+            }
+            cells.extend(row);
+            height += 1;
+        }
+
+        Ok(Self {
+            cells,
+            width,
+            height,
+        })
+    }
+
+    /// Builds a grid from rows of any length, filling the cells missing from
+    /// rows shorter than the longest one with `T::default()`.
+    pub fn from_ragged_rows(rows: Vec<Vec<T>>) -> Self
+    where
+        T: Default,
+    {
+        let width = rows.iter().map(Vec::len).max().unwrap_or_default();
+        let height = rows.len();
+        let mut cells = Vec::with_capacity(width * height);
+        for row in rows {
+            let missing = width - row.len();
+            cells.extend(row);
+            cells.extend(std::iter::repeat_with(T::default).take(missing));
+        }
+
+        Self {
+            cells,
+            width,
+            height,
+        }
+    }
+
+    /// A `width` by `height` grid with every cell set to `value`.
+    pub fn filled(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            cells: vec![value; width * height],
+            width,
+            height,
+        }
+    }
+
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        self.offset(point).is_some()
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.offset(point).map(|offset| &self.cells[offset])
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        self.offset(point).map(|offset| &mut self.cells[offset])
+    }
+
+    fn offset(&self, point: Point) -> Option<usize> {
+        let x = usize::try_from(point.x).ok()?;
+        let y = usize::try_from(point.y).ok()?;
+        (x < self.width && y < self.height).then_some(y * self.width + x)
+    }
+
+    #[allow(clippy::cast_possible_wrap)]
+    const fn point(&self, offset: usize) -> Point {
+        Point::new(
+            (offset % self.width) as isize,
+            (offset / self.width) as isize,
+        )
+    }
+
+    /// Every cell with its position, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(offset, cell)| (self.point(offset), cell))
+    }
+
+    /// The positions of all cells equal to `value`, in row-major order.
+    pub fn find_all(&self, value: &T) -> Vec<Point>
+    where
+        T: PartialEq,
+    {
+        self.iter()
+            .filter(|(_, cell)| *cell == value)
+            .map(|(point, _)| point)
+            .collect()
+    }
+
+    /// The cells above, right of, below and left of `point` that lie inside the grid.
+    pub fn neighbours4(&self, point: Point) -> impl Iterator<Item = (Point, &T)> {
+        self.neighbours(point, Direction4::ALL.map(Direction4::delta))
+    }
+
+    /// Like [`Self::neighbours4`], including the diagonal neighbours.
+    pub fn neighbours8(&self, point: Point) -> impl Iterator<Item = (Point, &T)> {
+        self.neighbours(point, Direction8::ALL.map(Direction8::delta))
+    }
+
+    fn neighbours<const N: usize>(
+        &self,
+        point: Point,
+        deltas: [Point; N],
+    ) -> impl Iterator<Item = (Point, &T)> {
+        deltas.into_iter().filter_map(move |delta| {
+            let neighbour = point.checked_add(delta, self.width, self.height)?;
+            Some((neighbour, &self[neighbour]))
+        })
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks_exact` rejects a zero chunk size, a grid without columns yields no rows.
+        self.cells.chunks_exact(self.width.max(1))
+    }
+
+    pub fn row(&self, y: usize) -> Option<&[T]> {
+        (y < self.height).then(|| &self.cells[y * self.width..(y + 1) * self.width])
+    }
+
+    /// The cells of column `x` from top to bottom, empty if there is no such column.
+    pub fn column(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> {
+        let cells = if x < self.width {
+            &self.cells[x..]
+        } else {
+            &[]
+        };
+        cells.iter().step_by(self.width.max(1))
+    }
+
+    /// The cells from `start` towards the bottom right, starting with `start` itself.
+    pub fn diagonal(&self, start: Point) -> impl Iterator<Item = (Point, &T)> {
+        self.ray(start, Direction8::SouthEast)
+    }
+
+    /// The cells from `start` towards the bottom left, starting with `start` itself.
+    pub fn anti_diagonal(&self, start: Point) -> impl Iterator<Item = (Point, &T)> {
+        self.ray(start, Direction8::SouthWest)
+    }
+
+    /// The cells from `start` towards `direction` until the edge of the grid,
+    /// starting with `start` itself.
+    pub fn ray(&self, start: Point, direction: Direction8) -> impl Iterator<Item = (Point, &T)> {
+        std::iter::successors(Some(start), move |point| Some(*point + direction.delta()))
+            .map_while(|point| self.get(point).map(|cell| (point, cell)))
+    }
+
+    /// A grid of the same size with `f` applied to every cell.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &T {
+        self.get(point).expect("point outside of the grid")
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut T {
+        self.get_mut(point).expect("point outside of the grid")
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    /// Writes one line per row without separators between cells.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::char_grid;
+
+    #[test]
+    fn test_grid() {
+        let grid = char_grid("abc\r\ndef\r\n\r\n").unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.get(Point::new(2, 1)), Some(&'f'));
+        assert_eq!(grid[Point::new(0, 1)], 'd');
+        assert_eq!(grid.get(Point::new(3, 0)), None);
+        assert_eq!(grid.get(Point::new(-1, 0)), None);
+        assert_eq!(grid.to_string(), "abc\ndef");
+
+        assert_eq!(
+            Grid::from_rows(vec![vec!['a', 'b', 'c'], vec!['d', 'e']]),
+            Err(ShapeError {
+                line: 2,
+                expected: 3,
+                found: 2,
+            })
+        );
+
+        let ragged = Grid::from_ragged_rows(vec![vec![1, 2, 3], vec![4]]);
+        assert_eq!(ragged.row(1), Some(&[4, 0, 0][..]));
+        assert_eq!(ragged.row(2), None);
+
+        let empty = char_grid("").unwrap();
+        assert_eq!((empty.width(), empty.height()), (0, 0));
+        assert_eq!(empty.rows().count(), 0);
+        assert_eq!(empty.to_string(), "");
+    }
+
+    #[test]
+    fn test_grid_iterators() {
+        let grid = char_grid("abc\ndef\nghi").unwrap();
+
+        let neighbours: String = grid.neighbours4(Point::new(0, 0)).map(|(_, c)| c).collect();
+        assert_eq!(neighbours, "bd");
+        let neighbours: String = grid.neighbours8(Point::new(1, 1)).map(|(_, c)| c).collect();
+        assert_eq!(neighbours, "bcfihgda");
+        assert_eq!(grid.neighbours8(Point::new(2, 2)).count(), 3);
+
+        let rows: Vec<String> = grid.rows().map(|row| row.iter().collect()).collect();
+        assert_eq!(rows, vec!["abc", "def", "ghi"]);
+        assert_eq!(grid.column(1).collect::<String>(), "beh");
+        assert_eq!(grid.column(3).count(), 0);
+        let diagonal: String = grid.diagonal(Point::new(0, 0)).map(|(_, c)| c).collect();
+        assert_eq!(diagonal, "aei");
+        let anti_diagonal: String = grid
+            .anti_diagonal(Point::new(2, 0))
+            .map(|(_, c)| c)
+            .collect();
+        assert_eq!(anti_diagonal, "ceg");
+        assert_eq!(grid.ray(Point::new(0, 3), Direction8::East).count(), 0);
+
+        let grid = char_grid("#.#\n..#").unwrap();
+        assert_eq!(
+            grid.find_all(&'#'),
+            vec![Point::new(0, 0), Point::new(2, 0), Point::new(2, 1)]
+        );
+        let mut numbers = grid.map(|c| u8::from(*c == '#'));
+        numbers[Point::new(1, 1)] = 7;
+        assert_eq!(numbers.to_string(), "101\n071");
+        assert_eq!(Grid::filled(2, 1, 'x').to_string(), "xx");
+    }
+}
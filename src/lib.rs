@@ -4,3 +4,6 @@ pub mod day03;
 pub mod day04;
 pub mod day05;
 pub mod day06;
+pub mod geometry;
+pub mod grid;
+pub mod parse;
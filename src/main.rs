@@ -1,8 +1,15 @@
 use adventofcode_24::{
-    day01::solve_day01, day02::solve_day02, day03::solve_day03, day04::solve_day04,
-    day05::solve_day05, day06::solve_day06,
+    day01::solve_day01,
+    day02::{SafetyPolicy, Trend, solve_day02_with_policy},
+    day03::{diagnose_day03, highlight_day03, operand::OperandPolicy, solve_day03_with_policy},
+    day04::{generate_day04, generator::PuzzleSpec, solve_day04, solve_dictionary_day04},
+    day05::{
+        explain_day05, export_dot_day05, middle::MiddlePolicy, repair_day05,
+        solve_day05_with_policy,
+    },
+    day06::solve_day06,
 };
-use clap::{Arg, Command};
+use clap::{Arg, ArgAction, Command, value_parser};
 
 fn main() {
     let matches = Command::new("aoc")
@@ -19,21 +26,143 @@ fn main() {
                 .short('2')
                 .help("Path to Day 02 Input file"),
         )
+        .arg(
+            Arg::new("min-step")
+                .long("min-step")
+                .value_parser(value_parser!(i32))
+                .default_value("1")
+                .help("Day 02: Minimum difference between adjacent levels"),
+        )
+        .arg(
+            Arg::new("max-step")
+                .long("max-step")
+                .value_parser(value_parser!(i32))
+                .default_value("3")
+                .help("Day 02: Maximum difference between adjacent levels"),
+        )
+        .arg(
+            Arg::new("trend")
+                .long("trend")
+                .value_parser(value_parser!(Trend))
+                .default_value("either")
+                .help("Day 02: Allowed direction (increasing, decreasing, either)"),
+        )
+        .arg(
+            Arg::new("tolerance")
+                .long("tolerance")
+                .value_parser(value_parser!(usize))
+                .default_value("1")
+                .help("Day 02: Number of levels that may be removed in part two"),
+        )
         .arg(
             Arg::new("day03")
                 .short('3')
                 .help("Path to Day 03 Input file"),
         )
+        .arg(
+            Arg::new("lenient")
+                .long("lenient")
+                .action(ArgAction::SetTrue)
+                .help("Day 03: Accept signed operands of up to 10 digits that fit into an i32"),
+        )
+        .arg(
+            Arg::new("highlight")
+                .long("highlight")
+                .action(ArgAction::SetTrue)
+                .help("Day 03: Print the memory with instructions highlighted"),
+        )
+        .arg(
+            Arg::new("warnings")
+                .long("warnings")
+                .action(ArgAction::SetTrue)
+                .help("Day 03: Report near-miss instructions as warnings"),
+        )
         .arg(
             Arg::new("day04")
                 .short('4')
                 .help("Path to Day 04 Input file"),
         )
+        .arg(
+            Arg::new("dictionary")
+                .long("dictionary")
+                .help("Day 04: Path to a dictionary file to find every listed word in the grid"),
+        )
+        .arg(
+            Arg::new("generate")
+                .long("generate")
+                .value_parser(parse_size)
+                .help("Day 04: Generate a word search grid of the given size (e.g. 10x10)"),
+        )
+        .arg(
+            Arg::new("word")
+                .long("word")
+                .default_value("XMAS")
+                .help("Day 04: Word to place when generating a grid"),
+        )
+        .arg(
+            Arg::new("count")
+                .long("count")
+                .value_parser(value_parser!(usize))
+                .default_value("1")
+                .help("Day 04: Number of occurrences of the word in the generated grid"),
+        )
+        .arg(
+            Arg::new("crosses")
+                .long("crosses")
+                .value_parser(value_parser!(usize))
+                .help("Day 04: Number of X-MAS crosses in the generated grid"),
+        )
+        .arg(
+            Arg::new("seed")
+                .long("seed")
+                .value_parser(value_parser!(u64))
+                .default_value("0")
+                .help("Day 04: Seed for generating a grid"),
+        )
         .arg(
             Arg::new("day05")
                 .short('5')
                 .help("Path to Day 05 Input file"),
         )
+        .arg(
+            Arg::new("explain")
+                .long("explain")
+                .action(ArgAction::SetTrue)
+                .help("Day 05: List the rules violated by every incorrectly ordered update"),
+        )
+        .arg(
+            Arg::new("dot")
+                .long("dot")
+                .action(ArgAction::SetTrue)
+                .help("Day 05: Print the rules as a Graphviz DOT graph"),
+        )
+        .arg(
+            Arg::new("update")
+                .long("update")
+                .value_parser(value_parser!(usize))
+                .help("Day 05: Restrict the DOT graph to this update (counted from 1)"),
+        )
+        .arg(
+            Arg::new("reduce")
+                .long("reduce")
+                .action(ArgAction::SetTrue)
+                .help("Day 05: Apply a transitive reduction to the DOT graph"),
+        )
+        .arg(
+            Arg::new("repair")
+                .long("repair")
+                .action(ArgAction::SetTrue)
+                .help("Day 05: Show how many moves and orderings every incorrectly ordered update has"),
+        )
+        .arg(
+            Arg::new("middle")
+                .long("middle")
+                .value_parser(value_parser!(MiddlePolicy))
+                .default_value("error")
+                .help(
+                    "Day 05: Handling of updates without a single middle page (error, skip, lower, upper, average)",
+                ),
+        )
         .arg(
             Arg::new("day06")
                 .short('6')
@@ -49,7 +178,13 @@ fn main() {
     }
 
     if let Some(file) = matches.get_one::<String>("day02") {
-        match solve_day02(file) {
+        let policy = SafetyPolicy::new(
+            *matches.get_one::<i32>("min-step").expect("has default"),
+            *matches.get_one::<i32>("max-step").expect("has default"),
+            *matches.get_one::<Trend>("trend").expect("has default"),
+            *matches.get_one::<usize>("tolerance").expect("has default"),
+        );
+        match policy.and_then(|policy| solve_day02_with_policy(file, &policy)) {
             Ok((r1, r2)) => {
                 println!("Result of Day 02:\nPart one: {r1}\nPart two: {r2}");
             }
@@ -58,7 +193,28 @@ fn main() {
     }
 
     if let Some(file) = matches.get_one::<String>("day03") {
-        match solve_day03(file) {
+        let policy = if matches.get_flag("lenient") {
+            OperandPolicy::lenient()
+        } else {
+            OperandPolicy::strict()
+        };
+        if matches.get_flag("highlight") {
+            match highlight_day03(file, &policy) {
+                Ok(highlighted) => println!("{highlighted}"),
+                Err(e) => eprintln!("{e}"),
+            }
+        }
+        if matches.get_flag("warnings") {
+            match diagnose_day03(file, &policy) {
+                Ok(diagnostics) => {
+                    for diagnostic in diagnostics {
+                        eprintln!("warning: {diagnostic}");
+                    }
+                }
+                Err(e) => eprintln!("{e}"),
+            }
+        }
+        match solve_day03_with_policy(file, &policy) {
             Ok((r1, r2)) => {
                 println!("Result of Day 03:\nPart one: {r1}\nPart two: {r2}");
             }
@@ -66,7 +222,38 @@ fn main() {
         }
     }
 
+    if let Some((width, height)) = matches.get_one::<(usize, usize)>("generate") {
+        let spec = PuzzleSpec {
+            width: *width,
+            height: *height,
+            word: matches
+                .get_one::<String>("word")
+                .expect("has default")
+                .clone(),
+            count: *matches.get_one::<usize>("count").expect("has default"),
+            crosses: matches.get_one::<usize>("crosses").copied(),
+            seed: *matches.get_one::<u64>("seed").expect("has default"),
+        };
+        match generate_day04(&spec) {
+            Ok(grid) => println!("{grid}"),
+            Err(e) => eprintln!("{e}"),
+        }
+    }
+
     if let Some(file) = matches.get_one::<String>("day04") {
+        if let Some(dictionary) = matches.get_one::<String>("dictionary") {
+            match solve_dictionary_day04(file, dictionary) {
+                Ok(found) => {
+                    for (word, location) in found {
+                        println!(
+                            "{word} at ({}, {}) heading {:?}",
+                            location.row, location.column, location.direction
+                        );
+                    }
+                }
+                Err(e) => eprintln!("{e}"),
+            }
+        }
         match solve_day04(file) {
             Ok((r1, r2)) => {
                 println!("Result of Day 04:\nPart one: {r1}\nPart two: {r2}");
@@ -76,9 +263,64 @@ fn main() {
     }
 
     if let Some(file) = matches.get_one::<String>("day05") {
-        match solve_day05(file) {
+        if matches.get_flag("dot") {
+            let update = matches.get_one::<usize>("update").copied();
+            match export_dot_day05(file, update, matches.get_flag("reduce")) {
+                Ok(dot) => print!("{dot}"),
+                Err(e) => eprintln!("{e}"),
+            }
+        }
+        if matches.get_flag("explain") {
+            match explain_day05(file) {
+                Ok(explanations) => {
+                    for explanation in explanations {
+                        let pages: Vec<String> =
+                            explanation.update.iter().map(ToString::to_string).collect();
+                        println!("Update {}:", pages.join(","));
+                        for violation in explanation.violations {
+                            println!("  {violation}");
+                        }
+                    }
+                }
+                Err(e) => eprintln!("{e}"),
+            }
+        }
+        if matches.get_flag("repair") {
+            match repair_day05(file) {
+                Ok(repairs) => {
+                    for repair in repairs {
+                        let pages: Vec<String> =
+                            repair.update.iter().map(ToString::to_string).collect();
+                        let orderings = repair
+                            .orderings
+                            .map_or_else(|| "too many to count".to_string(), |n| n.to_string());
+                        println!(
+                            "Update {}: moves: {}, valid orderings: {}",
+                            pages.join(","),
+                            repair.moves,
+                            orderings
+                        );
+                    }
+                }
+                Err(e) => eprintln!("{e}"),
+            }
+        }
+        let policy = *matches
+            .get_one::<MiddlePolicy>("middle")
+            .expect("has default");
+        match solve_day05_with_policy(file, policy) {
             Ok((r1, r2)) => {
-                println!("Result of Day 05:\nPart one: {r1}\nPart two: {r2}");
+                for update in r1.skipped.iter().chain(&r2.skipped) {
+                    let pages: Vec<String> = update.iter().map(ToString::to_string).collect();
+                    eprintln!(
+                        "warning: skipped update {} without a single middle page",
+                        pages.join(",")
+                    );
+                }
+                println!(
+                    "Result of Day 05 (middle page policy: {policy}):\nPart one: {}\nPart two: {}",
+                    r1.sum, r2.sum
+                );
             }
             Err(e) => eprintln!("{e}"),
         }
@@ -93,3 +335,10 @@ fn main() {
         }
     }
 }
+
+fn parse_size(size: &str) -> Result<(usize, usize), String> {
+    let (width, height) = size.split_once('x').unwrap_or((size, size));
+    let width = width.parse().map_err(|e| format!("{e}"))?;
+    let height = height.parse().map_err(|e| format!("{e}"))?;
+    Ok((width, height))
+}
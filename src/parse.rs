@@ -0,0 +1,384 @@
+use std::{error::Error, fmt, str::FromStr};
+
+use nom::{
+    IResult, Parser,
+    character::complete::{char, digit1, space1},
+    combinator::{all_consuming, map_res, opt, recognize},
+    error::{ErrorKind, VerboseError, VerboseErrorKind, context},
+    sequence::pair,
+};
+
+use crate::grid::Grid;
+
+pub type ParseResult<'a, T> = IResult<&'a str, T, VerboseError<&'a str>>;
+
+/// A line of puzzle input that could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The 1-based line number.
+    pub line: usize,
+    /// The 1-based column, in characters, where parsing failed.
+    pub column: usize,
+    /// What the parser expected at `column`.
+    pub expected: String,
+    /// The text of the line, without surrounding whitespace unless it is part
+    /// of the input, as in the rows of a [`char_grid`].
+    pub text: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid input on line {}, column {}: expected {} in '{}'",
+            self.line, self.column, self.expected, self.text
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+/// A line of the input. Lines from [`lines`] and [`sections`] are non-blank
+/// and without surrounding whitespace, rows from [`grid_lines`] are kept as they are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Line<'a> {
+    /// The 1-based line number.
+    pub number: usize,
+    /// The 1-based column of the first character of `text`.
+    pub column: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    fn new(index: usize, line: &'a str) -> Self {
+        let text = line.trim_start();
+        Self {
+            number: index + 1,
+            column: line[..line.len() - text.len()].chars().count() + 1,
+            text: text.trim_end(),
+        }
+    }
+
+    /// Parses the whole line with `parser`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `parser` fails or does not consume the whole line.
+    pub fn parse<T>(
+        &self,
+        parser: impl Parser<&'a str, T, VerboseError<&'a str>>,
+    ) -> Result<T, ParseError> {
+        all_consuming(parser)(self.text)
+            .map(|(_, value)| value)
+            .map_err(|error| self.error(&error))
+    }
+
+    fn error(&self, error: &nom::Err<VerboseError<&str>>) -> ParseError {
+        let errors = match error {
+            nom::Err::Error(e) | nom::Err::Failure(e) => e.errors.as_slice(),
+            nom::Err::Incomplete(_) => &[],
+        };
+
+        let position = errors
+            .first()
+            .map_or(self.text.len(), |(rest, _)| self.text.len() - rest.len());
+        let expected = errors
+            .iter()
+            .find_map(|(_, kind)| match kind {
+                VerboseErrorKind::Char(c) => Some(format!("'{c}'")),
+                VerboseErrorKind::Context(context) => Some((*context).to_string()),
+                VerboseErrorKind::Nom(ErrorKind::Eof) => Some("end of line".to_string()),
+                VerboseErrorKind::Nom(_) => None,
+            })
+            .unwrap_or_else(|| "valid input".to_string());
+
+        ParseError {
+            line: self.number,
+            column: self.column + self.text[..position].chars().count(),
+            expected,
+            text: self.text.to_string(),
+        }
+    }
+}
+
+/// The non-blank lines of `input`, accepting `\n` and `\r\n` line endings.
+pub fn lines(input: &str) -> Vec<Line<'_>> {
+    sections(input).into_iter().flatten().collect()
+}
+
+/// The non-blank lines of `input`, grouped into sections separated by one or
+/// more blank lines.
+///
+/// # Panics
+///
+/// Never panics: there is always a section to push a line to.
+pub fn sections(input: &str) -> Vec<Vec<Line<'_>>> {
+    let mut sections = vec![Vec::new()];
+    for (index, line) in input.lines().enumerate() {
+        let line = Line::new(index, line);
+        if !line.text.is_empty() {
+            sections.last_mut().expect("never empty").push(line);
+        } else if sections.last().is_some_and(|section| !section.is_empty()) {
+            sections.push(Vec::new());
+        }
+    }
+    sections.retain(|section| !section.is_empty());
+    sections
+}
+
+/// Parses every line with `parser`.
+///
+/// # Errors
+///
+/// This function will return an error for the first line `parser` does not fully consume.
+pub fn parse_lines<'a, T>(
+    lines: &[Line<'a>],
+    mut parser: impl Parser<&'a str, T, VerboseError<&'a str>>,
+) -> Result<Vec<T>, ParseError> {
+    lines
+        .iter()
+        .map(|line| line.parse(|input| parser.parse(input)))
+        .collect()
+}
+
+/// An integer without sign, e.g. `42`.
+///
+/// # Errors
+///
+/// Returns a nom error if `input` does not start with a digit or the digits
+/// do not fit into `T`.
+pub fn unsigned<T: FromStr>(input: &str) -> ParseResult<'_, T> {
+    context("unsigned integer", map_res(digit1, str::parse))(input)
+}
+
+/// An integer with an optional minus sign, e.g. `-42`.
+///
+/// # Errors
+///
+/// Returns a nom error if `input` does not start with a digit, optionally
+/// preceded by `-`, or the number does not fit into `T`.
+pub fn signed<T: FromStr>(input: &str) -> ParseResult<'_, T> {
+    context(
+        "integer",
+        map_res(recognize(pair(opt(char('-')), digit1)), str::parse),
+    )(input)
+}
+
+/// One or more `item`s separated by `separator`. A separator has to be
+/// followed by another item.
+///
+/// Like nom's `separated_list1`, the list ends where `separator` reports an
+/// error, while a failure of `separator` is passed on.
+fn separated<'a, T, S>(
+    mut item: impl Parser<&'a str, T, VerboseError<&'a str>>,
+    mut separator: impl Parser<&'a str, S, VerboseError<&'a str>>,
+) -> impl FnMut(&'a str) -> ParseResult<'a, Vec<T>> {
+    move |input| {
+        let (mut rest, first) = item.parse(input)?;
+        let mut items = vec![first];
+        loop {
+            let after = match separator.parse(rest) {
+                Ok((after, _)) => after,
+                Err(nom::Err::Error(_)) => return Ok((rest, items)),
+                Err(error) => return Err(error),
+            };
+            let (after, next) = item.parse(after)?;
+            items.push(next);
+            rest = after;
+        }
+    }
+}
+
+/// One or more `item`s separated by spaces or tabs, e.g. `7 6 4 2 1`.
+pub fn whitespace_list<'a, T>(
+    item: impl Parser<&'a str, T, VerboseError<&'a str>>,
+) -> impl FnMut(&'a str) -> ParseResult<'a, Vec<T>> {
+    separated(item, space1)
+}
+
+/// Exactly `N` `item`s separated by spaces or tabs, e.g. the two columns of `3   4`.
+pub fn columns<'a, T, const N: usize>(
+    mut item: impl Parser<&'a str, T, VerboseError<&'a str>>,
+) -> impl FnMut(&'a str) -> ParseResult<'a, [T; N]> {
+    move |input| {
+        let mut items = Vec::with_capacity(N);
+        let mut rest = input;
+        for index in 0..N {
+            if index > 0 {
+                (rest, _) = context("column separator", space1)(rest)?;
+            }
+            let (after, value) = item.parse(rest)?;
+            items.push(value);
+            rest = after;
+        }
+        match items.try_into() {
+            Ok(items) => Ok((rest, items)),
+            Err(_) => unreachable!("exactly N items are parsed"),
+        }
+    }
+}
+
+/// One or more `item`s separated by commas, e.g. `75,47,61`.
+pub fn comma_list<'a, T>(
+    item: impl Parser<&'a str, T, VerboseError<&'a str>>,
+) -> impl FnMut(&'a str) -> ParseResult<'a, Vec<T>> {
+    separated(item, char(','))
+}
+
+/// Two `item`s separated by a pipe, e.g. `47|53`.
+pub fn pipe_pair<'a, T>(
+    mut item: impl Parser<&'a str, T, VerboseError<&'a str>>,
+) -> impl FnMut(&'a str) -> ParseResult<'a, (T, T)> {
+    move |input| {
+        let (input, a) = item.parse(input)?;
+        let (input, _) = char('|')(input)?;
+        let (input, b) = item.parse(input)?;
+        Ok((input, (a, b)))
+    }
+}
+
+/// The rows of a grid: every line of `input` from the first to the last
+/// non-blank one, including leading and trailing spaces. Both `\n` and `\r\n`
+/// line endings are accepted.
+pub fn grid_lines(input: &str) -> Vec<Line<'_>> {
+    let rows: Vec<&str> = input.lines().collect();
+    let is_blank = |row: &&str| row.trim().is_empty();
+    let start = rows
+        .iter()
+        .position(|row| !is_blank(row))
+        .unwrap_or(rows.len());
+    let end = rows
+        .iter()
+        .rposition(|row| !is_blank(row))
+        .map_or(start, |index| index + 1);
+
+    rows[start..end]
+        .iter()
+        .enumerate()
+        .map(|(index, text)| Line {
+            number: start + index + 1,
+            column: 1,
+            text,
+        })
+        .collect()
+}
+
+/// Parses the [`grid_lines`] of `input` into a rectangular grid of characters.
+///
+/// # Errors
+///
+/// This function will return an error if a row, including an empty line
+/// between rows, differs in length from the first one.
+pub fn char_grid(input: &str) -> Result<Grid<char>, ParseError> {
+    let rows = grid_lines(input);
+    Grid::from_rows(rows.iter().map(|row| row.text.chars().collect())).map_err(|error| {
+        let row = rows[error.line - 1];
+        ParseError {
+            line: row.number,
+            column: error.found.min(error.expected) + 1,
+            expected: if error.found < error.expected {
+                format!("{} cells", error.expected)
+            } else {
+                "end of line".to_string()
+            },
+            text: row.text.to_string(),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nom::combinator::cut;
+
+    #[test]
+    fn test_sections() {
+        let input = "\r\n 47|53 \r\n97|13\r\n\r\n\r\n75,47\r\n\r\n97,13\r\n\r\n";
+        let got = sections(input);
+        assert_eq!(got.len(), 3);
+        assert_eq!(
+            got[0][0],
+            Line {
+                number: 2,
+                column: 2,
+                text: "47|53",
+            }
+        );
+        assert_eq!(got[2][0].number, 8);
+        assert_eq!(lines(input).len(), 4);
+        assert!(sections("\n \n").is_empty());
+    }
+
+    #[test]
+    fn test_combinators() {
+        let input = lines("47|53\n  3|-4\n1,2,x");
+
+        assert_eq!(input[0].parse(pipe_pair(unsigned::<i32>)), Ok((47, 53)));
+        assert_eq!(input[1].parse(pipe_pair(signed::<i32>)), Ok((3, -4)));
+        assert_eq!(
+            input[1].parse(pipe_pair(unsigned::<i32>)),
+            Err(ParseError {
+                line: 2,
+                column: 5,
+                expected: "unsigned integer".to_string(),
+                text: "3|-4".to_string(),
+            })
+        );
+
+        let err = input[2].parse(comma_list(unsigned::<i32>)).unwrap_err();
+        assert_eq!((err.line, err.column), (3, 5));
+        assert_eq!(
+            err.to_string(),
+            "Invalid input on line 3, column 5: expected unsigned integer in '1,2,x'"
+        );
+
+        let parsed = parse_lines(&lines("7 6 4\n1\t-2"), whitespace_list(signed::<i32>));
+        assert_eq!(parsed, Ok(vec![vec![7, 6, 4], vec![1, -2]]));
+
+        let line = lines("3   4")[0];
+        assert_eq!(line.parse(columns::<_, 2>(signed::<i32>)), Ok([3, 4]));
+        let err = lines("3 4 5")[0]
+            .parse(columns::<_, 2>(signed::<i32>))
+            .unwrap_err();
+        assert_eq!((err.column, err.expected.as_str()), (4, "end of line"));
+        let err = lines("3")[0]
+            .parse(columns::<_, 2>(signed::<i32>))
+            .unwrap_err();
+        assert_eq!((err.column, err.expected.as_str()), (2, "column separator"));
+
+        let err = lines("99999999999")[0].parse(signed::<i32>).unwrap_err();
+        assert_eq!((err.column, err.expected.as_str()), (1, "integer"));
+
+        // A separator that fails instead of erroring does not end the list.
+        let err = lines("1,2")[0]
+            .parse(separated(unsigned::<i32>, cut(char(','))))
+            .unwrap_err();
+        assert_eq!((err.column, err.expected.as_str()), (4, "','"));
+    }
+
+    #[test]
+    fn test_char_grid() {
+        let rows = grid_lines("\r\n  \n ab\r\n\nc \n \n");
+        let texts: Vec<(usize, &str)> = rows.iter().map(|row| (row.number, row.text)).collect();
+        assert_eq!(texts, vec![(3, " ab"), (4, ""), (5, "c ")]);
+
+        let grid = char_grid("\n..#\r\n#..\r\n\r\n").unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+
+        let grid = char_grid(" .#\n#. \n").unwrap();
+        assert_eq!(grid.to_string(), " .#\n#. ");
+
+        let err = char_grid("...\n\n...\n").unwrap_err();
+        assert_eq!(
+            (err.line, err.column, err.expected.as_str()),
+            (2, 1, "3 cells")
+        );
+        let err = char_grid("\n...\n..\n").unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (3, 3, ".."));
+        let err = char_grid("...\n....\n").unwrap_err();
+        assert_eq!(
+            (err.line, err.column, err.expected.as_str()),
+            (2, 4, "end of line")
+        );
+    }
+}